@@ -1,5 +1,5 @@
 //! Classic algorithms and data structures implemented in Rust programming language.
-#![feature(test)]
+#![cfg_attr(test, feature(test))]
 
 pub mod sort;
 pub mod structs;
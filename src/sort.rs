@@ -1,20 +1,173 @@
 //! Classic sorting algorithms implemented in Rust.
+//!
+//! Every sort accepts empty and single-element slices. If a comparator panics, the slice is left
+//! holding a permutation of the original elements.
 pub mod selection;
 pub mod insertion;
 pub mod shell;
 pub mod bubble;
 pub mod merge;
+pub mod merge_bottom_up;
+pub mod merge_natural;
+pub mod tim;
+pub mod par_merge;
 pub mod quick;
 pub mod quick_3_way;
+pub mod par_quick;
 pub mod flag;
+pub mod counting;
+pub mod partition;
 pub mod heap;
+pub mod intro;
+pub mod pdq;
+pub mod select;
+pub mod partial;
+pub mod kmerge;
+pub mod auto;
+pub mod argsort;
+pub mod external;
+pub mod radix;
+pub mod string;
+pub mod probe;
+pub mod sorter;
+pub mod stats;
+pub mod trace;
 
 pub use selection::*;
 pub use insertion::*;
 pub use shell::*;
 pub use bubble::*;
 pub use merge::*;
+pub use merge_bottom_up::*;
+pub use merge_natural::*;
+pub use tim::*;
+pub use par_merge::*;
 pub use quick::*;
 pub use quick_3_way::*;
+pub use par_quick::*;
 pub use flag::*;
-pub use heap::*;  
+pub use counting::*;
+pub use partition::*;
+pub use heap::*;
+pub use intro::*;
+pub use pdq::*;
+pub use select::*;
+pub use partial::*;
+pub use kmerge::*;
+pub use auto::*;
+pub use argsort::*;
+pub use radix::*;
+pub use string::*;
+pub use probe::*;
+pub use sorter::*;
+
+use std::cmp::Ordering;
+
+// Total ordering derived from `PartialOrd`, incomparable values are treated as equal.
+pub(crate) fn partial_compare<T>(a: &T, b: &T) -> Ordering where T: PartialOrd {
+    a.partial_cmp(b).unwrap_or(Ordering::Equal)
+}
+
+// Reorders the list so list[i] becomes the element previously at list[order[i]], following the cycles
+// of the permutation with swaps. `order` is left as the identity.
+pub(crate) fn apply_order<T>(list: &mut [T], order: &mut [usize]) {
+    follow_cycles(order, |i, j| list.swap(i, j));
+}
+
+// Calls `swap` for each exchange needed to apply the permutation as `apply_order` does.
+pub(crate) fn follow_cycles<F>(order: &mut [usize], mut swap: F) where F: FnMut(usize, usize) {
+    for i in 0..order.len() {
+        let mut j = i;
+        while order[j] != i {
+            let next = order[j];
+            order[j] = j;
+            swap(j, next);
+            j = next;
+        }
+        order[j] = j;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::panic::{self, AssertUnwindSafe};
+
+    type SortBy<T> = fn(&mut [T], &mut dyn FnMut(&T, &T) -> Ordering);
+
+    fn sorters() -> Vec<(&'static str, SortBy<String>)> {
+        vec![
+            ("bubble", |l, c| bubble_by(l, c)),
+            ("selection", |l, c| selection_by(l, c)),
+            ("insertion", |l, c| insertion_by(l, c)),
+            ("shell", |l, c| shell_by(l, c)),
+            ("merge", |l, c| merge_by(l, c)),
+            ("merge_bottom_up", |l, c| merge_bottom_up_by(l, c)),
+            ("merge_natural", |l, c| merge_natural_by(l, c)),
+            ("tim", |l, c| tim_by(l, c)),
+            ("quick", |l, c| quick_by(l, c)),
+            ("quick_3_way", |l, c| quick_3_way_by(l, c)),
+            ("heap", |l, c| heap_by(l, c)),
+            ("intro", |l, c| intro_by(l, c)),
+            ("pdq", |l, c| pdq_by(l, c)),
+        ]
+    }
+
+    // Distinct values, so a duplicated or lost element changes the sorted contents.
+    fn input(n: usize) -> Vec<String> {
+        (0..n).map(|i| format!("{:04}", (i * 7919) % 1009)).collect()
+    }
+
+    // Runs the sort with a comparator panicking on comparison number `limit`.
+    // Returns true if the sort panicked.
+    fn sort_with_panic<T>(sort: SortBy<T>, list: &mut [T], limit: usize) -> bool where T: Ord {
+        let mut count = 0;
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            sort(list, &mut |a: &T, b: &T| {
+                count += 1;
+                if count == limit {
+                    panic!("comparator panic");
+                }
+                a.cmp(b)
+            });
+        }));
+        result.is_err()
+    }
+
+    #[test]
+    fn empty_and_single() {
+        for (name, sort) in sorters() {
+            let mut list: Vec<String> = vec![];
+            sort(&mut list, &mut |a, b| a.cmp(b));
+            assert!(list.is_empty(), "{}", name);
+
+            let mut list = vec![String::from("a")];
+            sort(&mut list, &mut |a, b| a.cmp(b));
+            assert_eq!(list, vec!["a"], "{}", name);
+        }
+
+        let mut list: Vec<i32> = vec![];
+        flag(&mut list);
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    fn comparator_panic_keeps_permutation() {
+        for (name, sort) in sorters() {
+            for &limit in [1, 2, 7, 50, 300].iter() {
+                let original = input(60);
+                let mut list = original.clone();
+                let mut expected = original.clone();
+                expected.sort();
+                if !sort_with_panic(sort, &mut list, limit) {
+                    // finished before reaching the limit
+                    assert_eq!(list, expected, "{}", name);
+                    continue;
+                }
+
+                list.sort();
+                assert_eq!(list, expected, "{} lost elements after panic at comparison {}", name, limit);
+            }
+        }
+    }
+}
@@ -0,0 +1,188 @@
+use std::cmp::Ordering;
+use crate::sort::{follow_cycles, partial_compare, pdq_by, tim_by};
+
+/// Column that can be reordered by `apply_permutation`, whatever its element type.
+pub trait Permute {
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Exchanges the elements at the two positions.
+    fn swap(&mut self, i: usize, j: usize);
+}
+
+impl<T, const N: usize> Permute for [T; N] {
+    fn len(&self) -> usize {
+        N
+    }
+
+    fn swap(&mut self, i: usize, j: usize) {
+        self.as_mut_slice().swap(i, j);
+    }
+}
+
+impl<T> Permute for Vec<T> {
+    fn len(&self) -> usize {
+        Vec::len(self)
+    }
+
+    fn swap(&mut self, i: usize, j: usize) {
+        self.as_mut_slice().swap(i, j);
+    }
+}
+
+impl<T> Permute for Box<[T]> {
+    fn len(&self) -> usize {
+        <[T]>::len(self)
+    }
+
+    fn swap(&mut self, i: usize, j: usize) {
+        <[T]>::swap(self, i, j);
+    }
+}
+
+impl<T> Permute for &mut [T] {
+    fn len(&self) -> usize {
+        <[T]>::len(self)
+    }
+
+    fn swap(&mut self, i: usize, j: usize) {
+        <[T]>::swap(self, i, j);
+    }
+}
+
+/// Returns the indices of the list in sorted order of their elements, the list itself is not
+/// modified: `list[perm[0]]` is the smallest element. Stable, equal elements keep their index order.
+pub fn argsort<T>(list: &[T]) -> Vec<usize> where T: PartialOrd {
+    argsort_by(list, partial_compare)
+}
+
+/// Stable argsort with a comparator function.
+pub fn argsort_by<T, F>(list: &[T], mut compare: F) -> Vec<usize> where F: FnMut(&T, &T) -> Ordering {
+    let mut perm: Vec<usize> = (0..list.len()).collect();
+    tim_by(&mut perm, |&i, &j| compare(&list[i], &list[j]));
+    perm
+}
+
+/// Stable argsort by the key extracted from each element.
+pub fn argsort_by_key<T, K, F>(list: &[T], mut key: F) -> Vec<usize> where F: FnMut(&T) -> K, K: PartialOrd {
+    argsort_by(list, |a, b| partial_compare(&key(a), &key(b)))
+}
+
+/// Returns the indices of the list in sorted order of their elements without allocating
+/// beyond the result. Not stable.
+pub fn argsort_unstable<T>(list: &[T]) -> Vec<usize> where T: PartialOrd {
+    argsort_unstable_by(list, partial_compare)
+}
+
+/// Unstable argsort with a comparator function.
+pub fn argsort_unstable_by<T, F>(list: &[T], mut compare: F) -> Vec<usize> where F: FnMut(&T, &T) -> Ordering {
+    let mut perm: Vec<usize> = (0..list.len()).collect();
+    pdq_by(&mut perm, |&i, &j| compare(&list[i], &list[j]));
+    perm
+}
+
+/// Unstable argsort by the key extracted from each element.
+pub fn argsort_unstable_by_key<T, K, F>(list: &[T], mut key: F) -> Vec<usize> where F: FnMut(&T) -> K, K: PartialOrd {
+    argsort_unstable_by(list, |a, b| partial_compare(&key(a), &key(b)))
+}
+
+/// Reorders every slice in place so `slice[i]` becomes the element previously at `slice[perm[i]]`,
+/// as returned by `argsort`. Follows the cycles of the permutation, taking O(n) swaps per slice
+/// and O(n) extra memory for a copy of the permutation.
+/// Panics if `perm` is not a permutation of 0..n or a slice is not of its length.
+pub fn apply_permutation(slices: &mut [&mut dyn Permute], perm: &[usize]) {
+    let n = perm.len();
+    for slice in slices.iter() {
+        assert_eq!(slice.len(), n, "slice length does not match the permutation");
+    }
+    let mut seen = vec![false; n];
+    for &p in perm.iter() {
+        assert!(p < n && !seen[p], "not a permutation of 0..{}", n);
+        seen[p] = true;
+    }
+
+    let mut order = perm.to_vec();
+    follow_cycles(&mut order, |i, j| {
+        for slice in slices.iter_mut() {
+            slice.swap(i, j);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn argsort_stable() {
+        let list = [3, 1, 2, 1, 3, 0];
+        assert_eq!(argsort(&list), vec![5, 1, 3, 2, 0, 4]);
+        assert_eq!(argsort_by(&list, |a, b| b.cmp(a)), vec![0, 4, 2, 1, 3, 5]);
+        assert_eq!(argsort_by_key(&["bb", "a", "cc", "d"], |s| s.len()), vec![1, 3, 0, 2]);
+        assert!(argsort::<i32>(&[]).is_empty());
+    }
+
+    #[test]
+    fn argsort_unstable_sorts() {
+        let list: Vec<u32> = (0..1000).map(|i| (i * 7919) % 101).collect();
+        let perm = argsort_unstable(&list);
+        assert!(perm.windows(2).all(|w| list[w[0]] <= list[w[1]]));
+
+        let mut sorted_perm = perm.clone();
+        sorted_perm.sort();
+        assert_eq!(sorted_perm, (0..1000).collect::<Vec<usize>>());
+
+        let perm = argsort_unstable_by_key(&list, |&e| std::cmp::Reverse(e));
+        assert!(perm.windows(2).all(|w| list[w[0]] >= list[w[1]]));
+    }
+
+    #[test]
+    fn apply_permutation_columns() {
+        let mut ages = vec![41, 23, 35, 23];
+        let mut names = vec!["ann", "bob", "cid", "dan"];
+        let mut scores = [1.5, 2.5, 3.5, 4.5];
+
+        let perm = argsort(&ages);
+        apply_permutation(&mut [&mut ages, &mut names, &mut scores], &perm);
+        assert_eq!(ages, vec![23, 23, 35, 41]);
+        assert_eq!(names, vec!["bob", "dan", "cid", "ann"]);
+        assert_eq!(scores, [2.5, 4.5, 3.5, 1.5]);
+    }
+
+    #[test]
+    fn apply_permutation_sub_slice_and_box() {
+        let mut table = vec![9, 30, 10, 20, 9];
+        let mut labels: Box<[char]> = vec!['c', 'a', 'b'].into_boxed_slice();
+
+        let mut keys = &mut table[1..4];
+        let perm = argsort(keys);
+        apply_permutation(&mut [&mut keys, &mut labels], &perm);
+        assert_eq!(table, vec![9, 10, 20, 30, 9]);
+        assert_eq!(&labels[..], &['a', 'b', 'c']);
+    }
+
+    #[test]
+    fn apply_permutation_cycles() {
+        let perm: Vec<usize> = (0..100).map(|i| (i * 37) % 100).collect();
+        let mut list: Vec<usize> = (100..200).collect();
+        apply_permutation(&mut [&mut list], &perm);
+        assert_eq!(list, perm.iter().map(|&p| p + 100).collect::<Vec<usize>>());
+    }
+
+    #[test]
+    #[should_panic(expected = "not a permutation")]
+    fn apply_permutation_repeated_index() {
+        let mut list = vec![1, 2, 3];
+        apply_permutation(&mut [&mut list], &[0, 0, 2]);
+    }
+
+    #[test]
+    #[should_panic(expected = "slice length")]
+    fn apply_permutation_length_mismatch() {
+        let mut list = vec![1, 2, 3];
+        apply_permutation(&mut [&mut list], &[1, 0]);
+    }
+}
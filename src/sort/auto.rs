@@ -0,0 +1,277 @@
+use std::cmp::Ordering;
+use crate::sort::partial_compare;
+use crate::sort::insertion::insertion_by;
+use crate::sort::merge::merge_by;
+use crate::sort::quick::quick_by;
+use crate::sort::quick_3_way::quick_3_way_by;
+
+// Lists of this size or smaller are sorted with insertion sort without looking at them.
+const TINY: usize = 32;
+// Largest number of elements sampled to profile the input.
+const MAX_SAMPLE: usize = 32;
+// Largest fraction of sampled pairs out of order for the input to count as nearly sorted.
+const NEARLY_SORTED: f64 = 0.02;
+// Smallest fraction of equal neighbours in the sorted sample for the input to count as duplicate-heavy.
+const DUPLICATES: f64 = 0.5;
+// Swaps per element insertion sort may take on a nearly sorted input before giving up.
+const INSERTION_BUDGET: usize = 4;
+
+/// Algorithm picked by `auto`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AutoChoice {
+    Insertion,
+    Quick3Way,
+    Merge,
+    Quick,
+}
+
+/// Presortedness and duplicate density of a list estimated from a sample of its elements.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InputProfile {
+    pub len: usize,
+    /// Number of sampled elements.
+    pub sample: usize,
+    /// Estimated number of non-decreasing runs.
+    pub runs: usize,
+    /// Fraction of sampled pairs out of order: 0 for sorted, 1 for strictly descending input.
+    pub inversions: f64,
+    /// Fraction of sampled elements equal to their neighbour in sorted order.
+    pub duplicates: f64,
+}
+
+/// Sorts the list with the algorithm that suits it best, returns the algorithm that sorted it:
+/// insertion sort for tiny or nearly sorted lists, 3-way quick sort for lists with many duplicates
+/// and quick sort otherwise. Sampling the input takes at most 1056 comparisons whatever its size,
+/// see `analyze`. Not stable.
+pub fn auto<T>(list: &mut [T]) -> AutoChoice where T: PartialOrd {
+    auto_by(list, partial_compare)
+}
+
+/// Adaptive sort with a comparator function.
+pub fn auto_by<T, F>(list: &mut [T], mut compare: F) -> AutoChoice where F: FnMut(&T, &T) -> Ordering {
+    let choice = dispatch(list, &mut compare, false);
+    match choice {
+        AutoChoice::Quick3Way => quick_3_way_by(list, compare),
+        AutoChoice::Quick => quick_by(list, compare),
+        _ => {}
+    }
+    choice
+}
+
+/// Stable adaptive sort: insertion sort for tiny or nearly sorted lists, merge sort otherwise.
+pub fn auto_stable<T>(list: &mut [T]) -> AutoChoice where T: PartialOrd {
+    auto_stable_by(list, partial_compare)
+}
+
+/// Stable adaptive sort with a comparator function.
+pub fn auto_stable_by<T, F>(list: &mut [T], mut compare: F) -> AutoChoice where F: FnMut(&T, &T) -> Ordering {
+    let choice = dispatch(list, &mut compare, true);
+    if choice == AutoChoice::Merge {
+        merge_by(list, compare);
+    }
+    choice
+}
+
+/// Profiles the list from a sample of s <= 32 evenly spaced elements. Counting the inversions
+/// and sorting the sample take up to s(s-1)/2 comparisons each, s^2 + s = 1056 in total at most.
+pub fn analyze<T>(list: &[T]) -> InputProfile where T: PartialOrd {
+    analyze_by(list, partial_compare)
+}
+
+/// Profiles the list with a comparator function.
+pub fn analyze_by<T, F>(list: &[T], mut compare: F) -> InputProfile where F: FnMut(&T, &T) -> Ordering {
+    let n = list.len();
+    let s = (n/16).clamp(2, MAX_SAMPLE).min(n);
+    if n < 2 {
+        return InputProfile { len: n, sample: n, runs: n, inversions: 0.0, duplicates: 0.0 };
+    }
+    let positions: Vec<usize> = (0..s).map(|k| k * (n-1) / s).collect();
+
+    // descents between sampled neighbours estimate the number of runs
+    let descents = positions.iter()
+        .filter(|&&p| compare(&list[p+1], &list[p]) == Ordering::Less)
+        .count();
+    let runs = 1 + descents * (n-1) / s;
+
+    let mut inversions = 0;
+    for (a, &i) in positions.iter().enumerate() {
+        for &j in positions[a+1..].iter() {
+            if compare(&list[j], &list[i]) == Ordering::Less {
+                inversions += 1;
+            }
+        }
+    }
+
+    let mut sorted = positions.clone();
+    insertion_by(&mut sorted, |&i, &j| compare(&list[i], &list[j]));
+    let equal = sorted.windows(2)
+        .filter(|w| compare(&list[w[0]], &list[w[1]]) == Ordering::Equal)
+        .count();
+
+    InputProfile {
+        len: n,
+        sample: s,
+        runs,
+        inversions: inversions as f64 / (s * (s-1) / 2) as f64,
+        duplicates: equal as f64 / (s-1) as f64,
+    }
+}
+
+/// Algorithm `auto` (or `auto_stable` if `stable`) picks for an input with the profile.
+pub fn choose(profile: &InputProfile, stable: bool) -> AutoChoice {
+    if profile.len <= TINY || is_nearly_sorted(profile) {
+        AutoChoice::Insertion
+    } else {
+        fallback(profile, stable)
+    }
+}
+
+fn is_nearly_sorted(profile: &InputProfile) -> bool {
+    profile.inversions <= NEARLY_SORTED && profile.runs <= profile.len/16 + 1
+}
+
+fn fallback(profile: &InputProfile, stable: bool) -> AutoChoice {
+    if stable {
+        AutoChoice::Merge
+    } else if profile.duplicates >= DUPLICATES {
+        AutoChoice::Quick3Way
+    } else {
+        AutoChoice::Quick
+    }
+}
+
+// Picks the algorithm and runs insertion sort if it was picked. A nearly sorted looking input
+// which turns out to need too many swaps is left to the fallback algorithm.
+fn dispatch<T, F>(list: &mut [T], compare: &mut F, stable: bool) -> AutoChoice where F: FnMut(&T, &T) -> Ordering {
+    if list.len() <= TINY {
+        insertion_by(list, &mut *compare);
+        return AutoChoice::Insertion;
+    }
+
+    let profile = analyze_by(list, &mut *compare);
+    let choice = choose(&profile, stable);
+    if choice != AutoChoice::Insertion {
+        return choice;
+    }
+    let limit = INSERTION_BUDGET * list.len();
+    if insertion_bounded(list, compare, limit) {
+        AutoChoice::Insertion
+    } else {
+        fallback(&profile, stable)
+    }
+}
+
+// Insertion sort giving up after `limit` swaps. Returns true if the list got sorted.
+fn insertion_bounded<T, F>(list: &mut [T], compare: &mut F, limit: usize) -> bool where F: FnMut(&T, &T) -> Ordering {
+    let mut swaps = 0;
+    for i in 1..list.len() {
+        for j in (1..i+1).rev() {
+            if compare(&list[j], &list[j-1]) != Ordering::Less {
+                break;
+            }
+            if swaps == limit {
+                return false;
+            }
+            list.swap(j, j-1);
+            swaps += 1;
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check_order<T>(list: &[T]) where T: PartialOrd + std::fmt::Debug {
+        let mut prev: &T = &list[0];
+        for a in list.iter() {
+            assert!(a >= prev, "non-decreasing order violation: {:?}", list);
+            prev = a;
+        }
+    }
+
+    fn pseudo_random(n: usize) -> Vec<i64> {
+        (0..n).map(|i| ((i as u64 * 2654435761) % 1000003) as i64).collect()
+    }
+
+    #[test]
+    fn auto_tiny_and_empty() {
+        let mut input: Vec<i32> = vec![];
+        assert_eq!(auto(&mut input), AutoChoice::Insertion);
+
+        let mut input = vec![3, 1, 2];
+        assert_eq!(auto(&mut input), AutoChoice::Insertion);
+        assert_eq!(input, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn auto_nearly_sorted() {
+        let mut input: Vec<i64> = (0..10000).collect();
+        for i in (0..10000).step_by(500) {
+            input.swap(i, i+1);
+        }
+        assert_eq!(auto(&mut input), AutoChoice::Insertion);
+        check_order(&input);
+    }
+
+    #[test]
+    fn auto_duplicates_and_random() {
+        let mut input: Vec<i64> = pseudo_random(10000).into_iter().map(|e| e % 5).collect();
+        assert!(analyze(&input).duplicates > 0.8);
+        assert_eq!(auto(&mut input), AutoChoice::Quick3Way);
+        check_order(&input);
+
+        let mut input = pseudo_random(10000);
+        let profile = analyze(&input);
+        assert!(profile.inversions > 0.2 && profile.duplicates < 0.1, "{:?}", profile);
+        assert_eq!(auto(&mut input), AutoChoice::Quick);
+        check_order(&input);
+    }
+
+    #[test]
+    fn auto_gives_up_on_misleading_sample() {
+        // a long reversed block mostly between the sampled positions
+        let mut input: Vec<i64> = (0..10000).collect();
+        input[5000..5600].reverse();
+        assert_eq!(choose(&analyze(&input), false), AutoChoice::Insertion);
+        assert_eq!(auto(&mut input), AutoChoice::Quick);
+        assert_eq!(input, (0..10000).collect::<Vec<i64>>());
+    }
+
+    #[test]
+    fn auto_stable_by_key() {
+        let mut input: Vec<(i64, usize)> = pseudo_random(5000).into_iter().map(|e| e % 7).zip(0..).collect();
+        let mut expected = input.clone();
+        expected.sort_by_key(|e| e.0);
+        assert_eq!(auto_stable_by(&mut input, |a, b| a.0.cmp(&b.0)), AutoChoice::Merge);
+        assert_eq!(input, expected);
+    }
+
+    #[test]
+    fn analyze_bounded_comparisons() {
+        for &n in [2, 100, 1000, 100000].iter() {
+            // a descending sample takes the most comparisons to sort
+            let list: Vec<i64> = (0..n as i64).rev().collect();
+            let mut comparisons = 0;
+            let profile = analyze_by(&list, |a, b| {
+                comparisons += 1;
+                a.cmp(b)
+            });
+            let s = profile.sample;
+            assert!(comparisons <= s * s + s && comparisons <= 1056, "{} comparisons for n = {}", comparisons, n);
+        }
+    }
+
+    #[test]
+    fn analyze_runs() {
+        let sorted: Vec<i64> = (0..1000).collect();
+        let profile = analyze(&sorted);
+        assert_eq!((profile.runs, profile.inversions, profile.duplicates), (1, 0.0, 0.0));
+
+        let descending: Vec<i64> = (0..1000).rev().collect();
+        let profile = analyze(&descending);
+        assert_eq!(profile.inversions, 1.0);
+        assert_eq!(profile.runs, 1000);
+    }
+}
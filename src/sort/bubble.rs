@@ -1,11 +1,30 @@
+use std::cmp::Ordering;
+use crate::sort::partial_compare;
+use crate::sort::probe::{swap, NoProbe, Probe};
+
 /// Bubble sort: run time O(n^2) worst case, O(n) in best case with early termination, space - O(1)
-pub fn bubble<T>(list: &mut [T]) where T: std::cmp::PartialOrd {
-    for i in 0..list.len()-1 {
+pub fn bubble<T>(list: &mut [T]) where T: PartialOrd {
+    bubble_by(list, partial_compare);
+}
+
+/// Bubble sort with a comparator function.
+pub fn bubble_by<T, F>(list: &mut [T], compare: F) where F: FnMut(&T, &T) -> Ordering {
+    bubble_probed(list, compare, &NoProbe);
+}
+
+/// Bubble sort with a key extraction function.
+pub fn bubble_by_key<T, K, F>(list: &mut [T], mut key: F) where F: FnMut(&T) -> K, K: PartialOrd {
+    bubble_by(list, |a, b| partial_compare(&key(a), &key(b)));
+}
+
+pub(crate) fn bubble_probed<T, F, P>(list: &mut [T], mut compare: F, probe: &P)
+    where F: FnMut(&T, &T) -> Ordering, P: Probe<T> + ?Sized {
+    for i in 1..list.len() {
         let mut swapped = false;
-        for j in 0..list.len()-i-1 {
-            if list[j+1] < list[j] {
+        for j in 0..list.len()-i {
+            if compare(&list[j+1], &list[j]) == Ordering::Less {
                 swapped = true;
-                list.swap(j, j+1);
+                swap(list, j, j+1, probe);
             }
         }
         if !swapped {
@@ -54,4 +73,29 @@ mod tests {
         bubble(&mut input);
         check_order(&input);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn bubble_by_desc() {
+        let mut input = vec![1, 3, 2, 5, 4];
+        bubble_by(&mut input, |a, b| b.cmp(a));
+        assert_eq!(input, vec![5, 4, 3, 2, 1]);
+    }
+
+    #[test]
+    fn bubble_by_key_field() {
+        let mut input = vec![(1, 'c'), (2, 'a'), (3, 'b')];
+        bubble_by_key(&mut input, |e| e.1);
+        assert_eq!(input, vec![(2, 'a'), (3, 'b'), (1, 'c')]);
+    }
+
+    #[test]
+    fn bubble_empty_and_single() {
+        let mut input: Vec<i32> = vec![];
+        bubble(&mut input);
+        assert!(input.is_empty());
+
+        let mut input = vec![1];
+        bubble(&mut input);
+        assert_eq!(input, vec![1]);
+    }
+}
@@ -0,0 +1,83 @@
+use crate::sort::apply_order;
+
+/// Key-indexed counting sort: stable sort by a key in 0..r, run time O(n + r), space - O(n + r).
+/// Keys are extracted once per element, elements are moved into place with swaps.
+/// Panics if a key is not less than `r`.
+pub fn counting_sort_by_key<T, F>(list: &mut [T], r: usize, mut key: F) where F: FnMut(&T) -> usize {
+    let keys: Vec<usize> = list.iter().map(|e| {
+        let k = key(e);
+        assert!(k < r, "key {} out of range 0..{}", k, r);
+        k
+    }).collect();
+
+    // count[k] becomes the first destination index for key k
+    let mut count = vec![0usize; r + 1];
+    for &k in keys.iter() {
+        count[k + 1] += 1;
+    }
+    for k in 0..r {
+        count[k + 1] += count[k];
+    }
+
+    let mut order = vec![0usize; list.len()];
+    for (i, &k) in keys.iter().enumerate() {
+        order[count[k]] = i;
+        count[k] += 1;
+    }
+    apply_order(list, &mut order);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    enum Status {
+        Active,
+        Pending,
+        Closed,
+    }
+
+    #[test]
+    fn counting_empty_and_single() {
+        let mut input: Vec<u8> = vec![];
+        counting_sort_by_key(&mut input, 4, |e| *e as usize);
+        assert!(input.is_empty());
+
+        let mut input = vec![3u8];
+        counting_sort_by_key(&mut input, 4, |e| *e as usize);
+        assert_eq!(input, vec![3]);
+    }
+
+    #[test]
+    fn counting_small_integers() {
+        let mut input: Vec<u8> = vec![2, 0, 1, 2, 2, 0, 1, 0, 9, 5];
+        counting_sort_by_key(&mut input, 10, |e| *e as usize);
+        assert_eq!(input, vec![0, 0, 0, 1, 1, 2, 2, 2, 5, 9]);
+    }
+
+    #[test]
+    fn counting_stable_enum_keys() {
+        use Status::*;
+        let mut input = vec![(Closed, "a"), (Active, "b"), (Pending, "c"), (Active, "d"), (Closed, "e"), (Pending, "f")];
+        counting_sort_by_key(&mut input, 3, |e| e.0 as usize);
+        assert_eq!(input, vec![(Active, "b"), (Active, "d"), (Pending, "c"), (Pending, "f"), (Closed, "a"), (Closed, "e")]);
+    }
+
+    #[test]
+    fn counting_bucketed_scores() {
+        let mut input: Vec<String> = (0..100).map(|i| format!("{:03}", (i * 37) % 101)).collect();
+        counting_sort_by_key(&mut input, 11, |s| s.parse::<usize>().unwrap() / 10);
+        let buckets: Vec<usize> = input.iter().map(|s| s.parse::<usize>().unwrap() / 10).collect();
+        let mut expected = buckets.clone();
+        expected.sort();
+        assert_eq!(buckets, expected);
+    }
+
+    #[test]
+    #[should_panic]
+    fn counting_key_out_of_range() {
+        let mut input = vec![1, 5];
+        counting_sort_by_key(&mut input, 5, |e| *e as usize);
+    }
+}
@@ -0,0 +1,464 @@
+//! External merge sort for inputs larger than memory: the input is read in chunks under a memory
+//! budget, each chunk is sorted with `tim` and spilled to a temporary file, then the sorted runs are
+//! merged into the output with `kmerge`, in several passes if there are too many of them to merge
+//! at once. The sort is stable.
+use std::cmp::Ordering;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+use crate::sort::{kmerge_by, partial_compare, tim_by};
+
+// Makes temporary file names unique within the process.
+static RUN_COUNTER: AtomicUsize = AtomicUsize::new(0);
+// Taken temporary file names skipped before giving up.
+const MAX_CREATE_ATTEMPTS: usize = 1000;
+// Largest buffer of a run file or of the output while merging.
+const MERGE_BUFFER: usize = 64 * 1024;
+// Smallest buffer worth merging one more run for, fewer runs are merged at once below it.
+const MIN_MERGE_BUFFER: usize = 4 * 1024;
+
+/// Layout of the records in the input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// Newline-delimited records, the newline is not part of the record. Every record is written
+    /// with a trailing newline, also the last one if the input lacks it.
+    Lines,
+    /// Binary records of the given number of bytes.
+    Fixed(usize),
+}
+
+/// Settings of the external sort.
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// Bytes held in memory by a chunk of records while it is sorted, including the merge buffer.
+    /// A single record larger than the budget is still read as a chunk of its own. While merging,
+    /// the budget is shared by the read buffers of the merged runs and the write buffer.
+    pub memory_budget: usize,
+    /// Directory for the sorted runs, they are removed once the sort is done.
+    pub temp_dir: PathBuf,
+    pub format: Format,
+    /// Most runs merged at once, at least 2. Every merged run keeps a file open, so this has to stay
+    /// well below the limit of open files of the process. More runs are merged in several passes.
+    pub max_merge_runs: usize,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            memory_budget: 64 * 1024 * 1024,
+            temp_dir: std::env::temp_dir(),
+            format: Format::Lines,
+            max_merge_runs: 16,
+        }
+    }
+}
+
+/// Sorts the records of the input in byte-wise order and writes them to the output.
+pub fn sort<R, W>(input: R, output: W, config: &Config) -> io::Result<()> where R: Read, W: Write {
+    sort_internal(input, || Ok(output), config, |a, b| a.cmp(b))
+}
+
+/// Sorts the records of the input by the key extracted from each record.
+/// Records with equal keys keep their input order.
+pub fn sort_by_key<R, W, K, F>(input: R, output: W, config: &Config, mut key: F) -> io::Result<()>
+    where R: Read, W: Write, F: FnMut(&[u8]) -> K, K: PartialOrd {
+    sort_internal(input, || Ok(output), config, |a, b| partial_compare(&key(a), &key(b)))
+}
+
+// The output is opened only once the whole input has been read.
+fn sort_internal<R, W, O, F>(input: R, open_output: O, config: &Config, mut compare: F) -> io::Result<()>
+    where R: Read, W: Write, O: FnOnce() -> io::Result<W>, F: FnMut(&[u8], &[u8]) -> Ordering {
+    if config.format == Format::Fixed(0) {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "record width must be positive"));
+    }
+
+    let mut reader = BufReader::new(input);
+    let mut runs = Runs { paths: Vec::new() };
+
+    let mut pending = None;
+    loop {
+        let mut chunk = read_chunk(&mut reader, config, &mut pending)?;
+        if chunk.is_empty() {
+            break;
+        }
+        tim_by(&mut chunk, |a, b| compare(a, b));
+
+        if runs.paths.is_empty() && pending.is_none() && reader.fill_buf()?.is_empty() {
+            // the whole input fits into memory, no spilling needed
+            drop(reader);
+            let mut writer = BufWriter::new(open_output()?);
+            for record in chunk.iter() {
+                write_record(&mut writer, record, config.format)?;
+            }
+            return writer.flush();
+        }
+        runs.spill(&chunk, config)?;
+    }
+    drop(reader);
+
+    let (fan_in, buffer) = merge_plan(config);
+    while runs.paths.len() > fan_in {
+        // merging neighbouring runs keeps records with equal keys in input order
+        let mut merged = Runs { paths: Vec::new() };
+        for group in runs.paths.chunks(fan_in) {
+            let (path, file) = create_run_file(&config.temp_dir)?;
+            merged.paths.push(path);
+            merge_runs(group, BufWriter::with_capacity(buffer, file), buffer, config.format, &mut compare)?;
+        }
+        runs = merged;
+    }
+    let writer = BufWriter::with_capacity(buffer, open_output()?);
+    merge_runs(&runs.paths, writer, buffer, config.format, &mut compare)
+}
+
+// Number of runs merged at once and the buffer size of each of them. The merged runs and the output
+// share the memory budget, down to a buffer of `MIN_MERGE_BUFFER` bytes.
+fn merge_plan(config: &Config) -> (usize, usize) {
+    let fan_in = (config.memory_budget / MIN_MERGE_BUFFER).saturating_sub(1)
+        .min(config.max_merge_runs)
+        .max(2);
+    let buffer = (config.memory_budget / (fan_in + 1)).clamp(1, MERGE_BUFFER);
+    (fan_in, buffer)
+}
+
+// Merges the sorted runs into the writer.
+fn merge_runs<W, F>(paths: &[PathBuf], mut writer: W, buffer: usize, format: Format, compare: &mut F) -> io::Result<()>
+    where W: Write, F: FnMut(&[u8], &[u8]) -> Ordering {
+    let mut sources = Vec::with_capacity(paths.len());
+    for path in paths.iter() {
+        sources.push(Records { reader: BufReader::with_capacity(buffer, File::open(path)?), format });
+    }
+    // read errors are ordered first so they surface right away
+    let merged = kmerge_by(sources, |a: &io::Result<Vec<u8>>, b: &io::Result<Vec<u8>>| match (a, b) {
+        (Ok(a), Ok(b)) => compare(a, b),
+        (Err(_), _) => Ordering::Less,
+        (_, Err(_)) => Ordering::Greater,
+    });
+    for record in merged {
+        write_record(&mut writer, &record?, format)?;
+    }
+    writer.flush()
+}
+
+/// Sorts the records of the input file into the output file. The output file is created only after
+/// the whole input has been read, so both paths may name the same file to sort it in place.
+pub fn sort_file<P, Q>(input: P, output: Q, config: &Config) -> io::Result<()> where P: AsRef<Path>, Q: AsRef<Path> {
+    sort_internal(File::open(input)?, || File::create(output), config, |a, b| a.cmp(b))
+}
+
+// Reads records until the next one would not fit into the memory budget or the input ends.
+// The budget covers the records, the slots of the chunk and the merge buffer of `tim`, which takes
+// up to half as many slots. A record that does not fit is left in `pending` for the next chunk,
+// the first record of a chunk is always taken.
+fn read_chunk<R>(reader: &mut R, config: &Config, pending: &mut Option<Vec<u8>>) -> io::Result<Vec<Vec<u8>>>
+    where R: BufRead {
+    let slot = mem::size_of::<Vec<u8>>();
+    let mut chunk: Vec<Vec<u8>> = Vec::new();
+    let mut bytes = 0;
+    loop {
+        let record = match pending.take() {
+            Some(record) => record,
+            None => match read_record(reader, config.format)? {
+                Some(record) => record,
+                None => break,
+            },
+        };
+        let len = chunk.len() + 1;
+        let slots = if len > chunk.capacity() { (2 * chunk.capacity()).max(4) } else { chunk.capacity() };
+        let used = bytes + record.capacity() + (slots + len.div_ceil(2)) * slot;
+        if used > config.memory_budget && !chunk.is_empty() {
+            *pending = Some(record);
+            break;
+        }
+        chunk.reserve_exact(slots - chunk.len());
+        bytes += record.capacity();
+        chunk.push(record);
+    }
+    Ok(chunk)
+}
+
+// Reads the next record, returns `None` at the end of the input.
+fn read_record<R>(reader: &mut R, format: Format) -> io::Result<Option<Vec<u8>>> where R: BufRead {
+    match format {
+        Format::Lines => {
+            let mut record = Vec::new();
+            if reader.read_until(b'\n', &mut record)? == 0 {
+                return Ok(None);
+            }
+            if record.last() == Some(&b'\n') {
+                record.pop();
+            }
+            Ok(Some(record))
+        }
+        Format::Fixed(width) => {
+            if reader.fill_buf()?.is_empty() {
+                return Ok(None);
+            }
+            let mut record = vec![0; width];
+            reader.read_exact(&mut record).map_err(|e| match e.kind() {
+                io::ErrorKind::UnexpectedEof => io::Error::new(io::ErrorKind::InvalidData, "truncated fixed-width record"),
+                _ => e,
+            })?;
+            Ok(Some(record))
+        }
+    }
+}
+
+fn write_record<W>(writer: &mut W, record: &[u8], format: Format) -> io::Result<()> where W: Write {
+    writer.write_all(record)?;
+    if format == Format::Lines {
+        writer.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+// Sorted runs spilled to temporary files, removed when dropped.
+struct Runs {
+    paths: Vec<PathBuf>,
+}
+
+impl Runs {
+    fn spill(&mut self, chunk: &[Vec<u8>], config: &Config) -> io::Result<()> {
+        let (path, file) = create_run_file(&config.temp_dir)?;
+        // registered before writing, so a failed write still removes the file
+        self.paths.push(path);
+
+        let mut writer = BufWriter::new(file);
+        for record in chunk.iter() {
+            write_record(&mut writer, record, config.format)?;
+        }
+        writer.flush()
+    }
+}
+
+// Creates a fresh file for a run. Names already taken, also by symlinks, are skipped instead of opened,
+// so files of other users of a shared directory are never followed or truncated.
+fn create_run_file(dir: &Path) -> io::Result<(PathBuf, File)> {
+    let mut attempts = 0;
+    loop {
+        let id = RUN_COUNTER.fetch_add(1, AtomicOrdering::Relaxed);
+        let path = dir.join(format!("sort-run-{}-{}.tmp", process::id(), id));
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(file) => return Ok((path, file)),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists && attempts < MAX_CREATE_ATTEMPTS => attempts += 1,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+impl Drop for Runs {
+    fn drop(&mut self) {
+        for path in self.paths.iter() {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+// Records of a sorted run.
+struct Records<R> {
+    reader: R,
+    format: Format,
+}
+
+impl<R> Iterator for Records<R> where R: BufRead {
+    type Item = io::Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        read_record(&mut self.reader, self.format).transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    // Config spilling after a few records into a fresh directory, so leftover files can be checked.
+    fn config(name: &str, memory_budget: usize, format: Format) -> Config {
+        let temp_dir = std::env::temp_dir().join(format!("external-sort-{}-{}", name, process::id()));
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&temp_dir).unwrap();
+        Config { memory_budget, temp_dir, format, ..Config::default() }
+    }
+
+    fn run_files(config: &Config) -> usize {
+        fs::read_dir(&config.temp_dir).unwrap().count()
+    }
+
+    #[test]
+    fn external_lines_with_spills() {
+        let lines: Vec<String> = (0..2000).map(|i| format!("line-{}", (i * 7919) % 2003)).collect();
+        let input = lines.join("\n");
+        let config = config("lines", 1000, Format::Lines);
+
+        let mut output = Vec::new();
+        sort(Cursor::new(input), &mut output, &config).unwrap();
+
+        let mut expected = lines.clone();
+        expected.sort();
+        assert_eq!(String::from_utf8(output).unwrap(), expected.join("\n") + "\n");
+        assert_eq!(run_files(&config), 0);
+        fs::remove_dir_all(&config.temp_dir).unwrap();
+    }
+
+    #[test]
+    fn external_chunks_within_budget() {
+        let config = Config { memory_budget: 4096, ..Config::default() };
+        let input: String = (0..1000).map(|i| "x".repeat(i % 50) + "\n").collect();
+        let mut reader = BufReader::new(Cursor::new(input));
+        let mut pending = None;
+        let mut records = 0;
+        loop {
+            let mut chunk = read_chunk(&mut reader, &config, &mut pending).unwrap();
+            if chunk.is_empty() {
+                break;
+            }
+            records += chunk.len();
+            tim_by(&mut chunk, |a, b| a.cmp(b));
+            let used: usize = chunk.iter().map(|r| r.capacity()).sum::<usize>()
+                + (chunk.capacity() + chunk.len().div_ceil(2)) * mem::size_of::<Vec<u8>>();
+            assert!(used <= config.memory_budget, "{} bytes for {} records", used, chunk.len());
+        }
+        assert_eq!(records, 1000);
+    }
+
+    #[test]
+    fn external_merge_passes() {
+        // 2-byte key followed by a 2-byte sequence number, spilled in a few hundred runs
+        let records: Vec<Vec<u8>> = (0..2000u16)
+            .map(|i| {
+                let mut r = ((u32::from(i) * 7919 % 40) as u16).to_be_bytes().to_vec();
+                r.extend_from_slice(&i.to_be_bytes());
+                r
+            })
+            .collect();
+        for &max_merge_runs in [2, 3, 16].iter() {
+            let config = Config { max_merge_runs, ..config("passes", 300, Format::Fixed(4)) };
+            let mut output = Vec::new();
+            sort_by_key(Cursor::new(records.concat()), &mut output, &config, |r| r[..2].to_vec()).unwrap();
+
+            let mut expected = records.clone();
+            expected.sort_by_key(|r| r[..2].to_vec());
+            assert_eq!(output, expected.concat());
+            assert_eq!(run_files(&config), 0);
+            fs::remove_dir_all(&config.temp_dir).unwrap();
+        }
+    }
+
+    #[test]
+    fn external_merge_plan() {
+        for &(memory_budget, max_merge_runs) in [(300, 16), (64 * 1024, 16), (64 * 1024, 4), (1 << 30, 100)].iter() {
+            let config = Config { memory_budget, max_merge_runs, ..Config::default() };
+            let (fan_in, buffer) = merge_plan(&config);
+            assert!(fan_in >= 2 && fan_in <= max_merge_runs, "{} runs", fan_in);
+            assert!((fan_in + 1) * buffer <= memory_budget, "{} runs of {} bytes", fan_in, buffer);
+        }
+        assert_eq!(merge_plan(&Config { memory_budget: 64 * 1024, max_merge_runs: 100, ..Config::default() }).0, 15);
+    }
+
+    #[test]
+    fn external_in_memory() {
+        let config = Config::default();
+        let mut output = Vec::new();
+        sort(Cursor::new("b\nc\na\n"), &mut output, &config).unwrap();
+        assert_eq!(output, b"a\nb\nc\n");
+
+        let mut output = Vec::new();
+        sort(Cursor::new(""), &mut output, &config).unwrap();
+        assert!(output.is_empty());
+    }
+
+    #[test]
+    fn external_fixed_width_by_key_stable() {
+        // 4-byte big-endian key followed by a 2-byte sequence number
+        let records: Vec<Vec<u8>> = (0..3000u32)
+            .map(|i| {
+                let mut r = ((i * 7919) % 50).to_be_bytes().to_vec();
+                r.extend_from_slice(&(i as u16).to_be_bytes());
+                r
+            })
+            .collect();
+        let input: Vec<u8> = records.concat();
+        let config = config("fixed", 2000, Format::Fixed(6));
+
+        let mut output = Vec::new();
+        sort_by_key(Cursor::new(input), &mut output, &config, |r| r[..4].to_vec()).unwrap();
+
+        let mut expected = records.clone();
+        expected.sort_by_key(|r| r[..4].to_vec());
+        assert_eq!(output, expected.concat());
+        assert_eq!(run_files(&config), 0);
+        fs::remove_dir_all(&config.temp_dir).unwrap();
+    }
+
+    #[test]
+    fn external_truncated_record() {
+        let config = config("truncated", 4, Format::Fixed(4));
+        let mut output = Vec::new();
+        let err = sort(Cursor::new(vec![1u8; 10]), &mut output, &config).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(run_files(&config), 0);
+        fs::remove_dir_all(&config.temp_dir).unwrap();
+
+        let config = Config { format: Format::Fixed(0), ..Config::default() };
+        let err = sort(Cursor::new(vec![1u8; 10]), &mut output, &config).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn external_skips_taken_names() {
+        let config = config("taken", 100, Format::Lines);
+        let next = RUN_COUNTER.load(AtomicOrdering::Relaxed);
+        let taken: Vec<PathBuf> = (next..next+20)
+            .map(|id| config.temp_dir.join(format!("sort-run-{}-{}.tmp", process::id(), id)))
+            .collect();
+        for path in taken.iter() {
+            fs::write(path, "keep").unwrap();
+        }
+
+        let lines: Vec<String> = (0..200).map(|i| format!("{:03}", (i * 7) % 200)).collect();
+        let mut output = Vec::new();
+        sort(Cursor::new(lines.join("\n")), &mut output, &config).unwrap();
+
+        let mut expected = lines.clone();
+        expected.sort();
+        assert_eq!(String::from_utf8(output).unwrap(), expected.join("\n") + "\n");
+        for path in taken.iter() {
+            assert_eq!(fs::read_to_string(path).unwrap(), "keep");
+        }
+        assert_eq!(run_files(&config), taken.len());
+        fs::remove_dir_all(&config.temp_dir).unwrap();
+    }
+
+    #[test]
+    fn external_sort_file() {
+        let config = config("file", 64, Format::Lines);
+        let input = config.temp_dir.join("input.txt");
+        let output = config.temp_dir.join("output.txt");
+        fs::write(&input, "delta\nalpha\ncharlie\nbravo\necho\nalpha").unwrap();
+
+        sort_file(&input, &output, &config).unwrap();
+        assert_eq!(fs::read_to_string(&output).unwrap(), "alpha\nalpha\nbravo\ncharlie\ndelta\necho\n");
+        fs::remove_dir_all(&config.temp_dir).unwrap();
+    }
+
+    #[test]
+    fn external_sort_file_in_place() {
+        let lines: Vec<String> = (0..500).map(|i| format!("{:03}", (i * 7) % 500)).collect();
+        for &memory_budget in [64, 1 << 20].iter() {
+            let config = config("in-place", memory_budget, Format::Lines);
+            let path = config.temp_dir.join("data.txt");
+            fs::write(&path, lines.join("\n")).unwrap();
+
+            sort_file(&path, &path, &config).unwrap();
+            let mut expected = lines.clone();
+            expected.sort();
+            assert_eq!(fs::read_to_string(&path).unwrap(), expected.join("\n") + "\n");
+            assert_eq!(run_files(&config), 1);
+            fs::remove_dir_all(&config.temp_dir).unwrap();
+        }
+    }
+}
@@ -1,21 +1,17 @@
+use crate::sort::partition::partition3_probed;
+use crate::sort::probe::{NoProbe, Probe};
+
 /// Dijkstra flag sort algorithm. Sorts a list of only 3 values {0, 1, 2} in O(n) time without additional memory usage.
-pub fn flag(list: &mut [i32]) {    
-    let mut l = 0;
-    let mut i = 0;
-    let mut g = list.len()-1;
-    let p = 1;
-    while i < g {
-        if list[i] < p {
-            list.swap(l, i);
-            l += 1;
-            i += 1;
-        } else if list[i] > p {
-            list.swap(i, g);
-            g -= 1;
-        } else {
-            i += 1;    
-        }
-    }
+pub fn flag(list: &mut [i32]) {
+    flag_probed(list, &NoProbe);
+}
+
+pub(crate) fn flag_probed<P>(list: &mut [i32], probe: &P) where P: Probe<i32> + ?Sized {
+    let pivot = 1;
+    partition3_probed(list, |e| {
+        probe.compare(e, &pivot);
+        e.cmp(&pivot)
+    }, probe);
 }
 
 #[cfg(test)]
@@ -71,4 +67,22 @@ mod test {
         flag(&mut input);
         check_order(&input);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn flag_empty_and_single() {
+        let mut input: Vec<i32> = vec![];
+        flag(&mut input);
+        assert!(input.is_empty());
+
+        let mut input = vec![1];
+        flag(&mut input);
+        assert_eq!(input, vec![1]);
+    }
+
+    #[test]
+    fn sort_last_element() {
+        let mut input = vec![1, 0];
+        flag(&mut input);
+        check_order(&input);
+    }
+}
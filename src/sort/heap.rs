@@ -1,10 +1,50 @@
-use crate::structs::heap::Heap;
+use std::cmp::Ordering;
+use crate::sort::partial_compare;
+use crate::sort::probe::{swap, NoProbe, Probe};
 
-/// Heap sort: O(n*logn) run time complexity, O(1) - space complexity.
-pub fn heap<T>(list: &mut [T]) where T: std::cmp::PartialOrd + Copy + Default {
-    let mut h: Heap<T> = Heap::from(list);
-    for e in list.iter_mut() {
-        *e = h.pop();
+/// Heap sort: O(n*logn) run time complexity, O(1) - space complexity, sorts the slice in place without allocations.
+pub fn heap<T>(list: &mut [T]) where T: PartialOrd {
+    heap_by(list, partial_compare);
+}
+
+/// Heap sort with a comparator function.
+pub fn heap_by<T, F>(list: &mut [T], compare: F) where F: FnMut(&T, &T) -> Ordering {
+    heap_probed(list, compare, &NoProbe);
+}
+
+/// Heap sort with a key extraction function.
+pub fn heap_by_key<T, K, F>(list: &mut [T], mut key: F) where F: FnMut(&T) -> K, K: PartialOrd {
+    heap_by(list, |a, b| partial_compare(&key(a), &key(b)));
+}
+
+pub(crate) fn heap_probed<T, F, P>(list: &mut [T], mut compare: F, probe: &P)
+    where F: FnMut(&T, &T) -> Ordering, P: Probe<T> + ?Sized {
+    let n = list.len();
+    // max-oriented heap built bottom-up in the slice itself
+    for k in (0..n/2).rev() {
+        sink(list, k, n, &mut compare, probe);
+    }
+    for n in (1..n).rev() {
+        swap(list, 0, n, probe);
+        sink(list, 0, n, &mut compare, probe);
+    }
+}
+
+// Moves list[i] down until it is not less than its children in the heap list[..n].
+pub(crate) fn sink<T, F, P>(list: &mut [T], i: usize, n: usize, compare: &mut F, probe: &P)
+    where F: FnMut(&T, &T) -> Ordering, P: Probe<T> + ?Sized {
+    let mut k = i;
+    while 2*k+1 < n {
+        let mut c = 2*k+1;
+        if c+1 < n && compare(&list[c], &list[c+1]) == Ordering::Less {
+            // choosing maximum of two children
+            c += 1;
+        }
+        if compare(&list[k], &list[c]) != Ordering::Less {
+            break;
+        }
+        swap(list, k, c, probe);
+        k = c;
     }
 }
 
@@ -47,5 +87,43 @@ mod tests {
         let mut input = vec![5, 4, 3, 2, 1];
         heap(&mut input);
         check_order(&input);
-    }    
-}
\ No newline at end of file
+    }
+
+    #[test]
+    fn heap_by_desc() {
+        let mut input = vec![1, 3, 2, 5, 4];
+        heap_by(&mut input, |a, b| b.cmp(a));
+        assert_eq!(input, vec![5, 4, 3, 2, 1]);
+    }
+
+    #[test]
+    fn heap_by_key_field() {
+        let mut input = vec![(1, 'c'), (2, 'a'), (3, 'b')];
+        heap_by_key(&mut input, |e| e.1);
+        assert_eq!(input, vec![(2, 'a'), (3, 'b'), (1, 'c')]);
+    }
+
+    #[test]
+    fn heap_strings() {
+        let mut input: Vec<String> = vec!["pear", "apple", "fig", "banana", "apple"]
+            .into_iter().map(String::from).collect();
+        heap(&mut input);
+        assert_eq!(input, vec!["apple", "apple", "banana", "fig", "pear"]);
+    }
+
+    #[test]
+    fn heap_boxed_records() {
+        #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+        struct Record {
+            id: u32,
+            name: String,
+        }
+
+        let mut input: Vec<Box<Record>> = (0..20)
+            .map(|i| Box::new(Record { id: (i * 7) % 20, name: format!("r{}", i) }))
+            .collect();
+        heap(&mut input);
+        check_order(&input);
+        assert_eq!(input.len(), 20);
+    }
+}
@@ -1,10 +1,30 @@
+use std::cmp::Ordering;
+use crate::sort::partial_compare;
+use crate::sort::probe::{swap, NoProbe, Probe};
+
 /// Insertion sort: run time O(n^2) average case, O(n) best case for sorted slices, space - O(1)
-pub fn insertion<T>(list: &mut [T]) where T: std::cmp::PartialOrd {
+pub fn insertion<T>(list: &mut [T]) where T: PartialOrd {
+    insertion_by(list, partial_compare);
+}
+
+/// Insertion sort with a comparator function.
+pub fn insertion_by<T, F>(list: &mut [T], compare: F) where F: FnMut(&T, &T) -> Ordering {
+    insertion_probed(list, compare, &NoProbe);
+}
+
+/// Insertion sort with a key extraction function.
+pub fn insertion_by_key<T, K, F>(list: &mut [T], mut key: F) where F: FnMut(&T) -> K, K: PartialOrd {
+    insertion_by(list, |a, b| partial_compare(&key(a), &key(b)));
+}
+
+pub(crate) fn insertion_probed<T, F, P>(list: &mut [T], mut compare: F, probe: &P)
+    where F: FnMut(&T, &T) -> Ordering, P: Probe<T> + ?Sized {
     for i in 1..list.len() {
         for j in (1..i+1).rev() {
-            if list[j] < list[j-1] {
-                list.swap(j, j-1);
+            if compare(&list[j], &list[j-1]) != Ordering::Less {
+                break;
             }
+            swap(list, j, j-1, probe);
         }
     }
 }
@@ -49,4 +69,29 @@ mod tests {
         insertion(&mut input);
         check_order(&input);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn insertion_by_desc() {
+        let mut input = vec![1, 3, 2, 5, 4];
+        insertion_by(&mut input, |a, b| b.cmp(a));
+        assert_eq!(input, vec![5, 4, 3, 2, 1]);
+    }
+
+    #[test]
+    fn insertion_by_key_stable() {
+        let mut input = vec![(1, 'c'), (2, 'a'), (3, 'c'), (4, 'a')];
+        insertion_by_key(&mut input, |e| e.1);
+        assert_eq!(input, vec![(2, 'a'), (4, 'a'), (1, 'c'), (3, 'c')]);
+    }
+
+    #[test]
+    fn insertion_sorted_linear_comparisons() {
+        let mut input: Vec<i32> = (0..100).collect();
+        let mut comparisons = 0;
+        insertion_by(&mut input, |a, b| {
+            comparisons += 1;
+            a.cmp(b)
+        });
+        assert_eq!(comparisons, 99);
+    }
+}
@@ -0,0 +1,123 @@
+use std::cmp::Ordering;
+use rand::Rng;
+use crate::sort::partial_compare;
+use crate::sort::insertion::insertion_probed;
+use crate::sort::heap::heap_probed;
+use crate::sort::quick::quick_partition;
+use crate::sort::probe::{NoProbe, Probe};
+
+// Ranges of this size or smaller are finished with insertion sort.
+const INSERTION_CUTOFF: usize = 16;
+
+/// Introsort: quick sort which switches to heap sort past 2*logn recursion depth and to insertion sort
+/// on small ranges. Run time O(n*logn) worst case, space - O(logn).
+pub fn intro<T>(list: &mut [T]) where T: PartialOrd {
+    intro_by(list, partial_compare);
+}
+
+/// Introsort with a comparator function.
+pub fn intro_by<T, F>(list: &mut [T], compare: F) where F: FnMut(&T, &T) -> Ordering {
+    intro_probed(list, compare, &NoProbe);
+}
+
+/// Introsort with a key extraction function.
+pub fn intro_by_key<T, K, F>(list: &mut [T], mut key: F) where F: FnMut(&T) -> K, K: PartialOrd {
+    intro_by(list, |a, b| partial_compare(&key(a), &key(b)));
+}
+
+pub(crate) fn intro_probed<T, F, P>(list: &mut [T], mut compare: F, probe: &P)
+    where F: FnMut(&T, &T) -> Ordering, P: Probe<T> + ?Sized {
+    let depth = 2 * (usize::BITS - list.len().leading_zeros()) as usize;
+    intro_internal(list, depth, &mut rand::thread_rng(), &mut compare, probe);
+}
+
+fn intro_internal<T, R, F, P>(list: &mut [T], depth: usize, rng: &mut R, compare: &mut F, probe: &P)
+    where R: Rng, F: FnMut(&T, &T) -> Ordering, P: Probe<T> + ?Sized {
+    probe.enter();
+    intro_loop(list, depth, rng, compare, probe);
+    probe.leave();
+}
+
+fn intro_loop<T, R, F, P>(mut list: &mut [T], mut depth: usize, rng: &mut R, compare: &mut F, probe: &P)
+    where R: Rng, F: FnMut(&T, &T) -> Ordering, P: Probe<T> + ?Sized {
+    loop {
+        if list.len() <= INSERTION_CUTOFF {
+            insertion_probed(list, &mut *compare, probe);
+            return;
+        }
+        if depth == 0 {
+            heap_probed(list, &mut *compare, probe);
+            return;
+        }
+        depth -= 1;
+
+        let p = quick_partition(list, 0, list.len()-1, rng, compare, probe);
+        let (left, right) = list.split_at_mut(p);
+        let right = &mut right[1..];
+
+        // recursing into the smaller side bounds the stack by O(logn), the larger side is handled by the loop
+        if left.len() < right.len() {
+            intro_internal(left, depth, rng, compare, probe);
+            list = right;
+        } else {
+            intro_internal(right, depth, rng, compare, probe);
+            list = left;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check_order<T>(list: &[T]) where T: PartialOrd + std::fmt::Debug {
+        let mut prev: &T = &list[0];
+        for a in list.iter() {
+            assert!(a >= prev, "non-decreasing order violation: {:?}", list);
+            prev = a;
+        }
+    }
+
+    #[test]
+    fn intro_two_elements() {
+        let mut input = vec![2, 1];
+        intro(&mut input);
+        check_order(&input);
+    }
+
+    #[test]
+    fn intro_basic_sort() {
+        let mut input = vec![1, 3, 2, 5, 4];
+        intro(&mut input);
+        check_order(&input);
+    }
+
+    #[test]
+    fn intro_large_sort() {
+        let mut input: Vec<i32> = (0..1000).map(|i| (i * 7919) % 1009).collect();
+        intro(&mut input);
+        check_order(&input);
+    }
+
+    #[test]
+    fn intro_all_equal() {
+        // degenerates quick sort partitions, heap sort has to take over
+        let mut input = vec![7; 10000];
+        intro(&mut input);
+        check_order(&input);
+    }
+
+    #[test]
+    fn intro_by_desc() {
+        let mut input: Vec<i32> = (0..100).collect();
+        intro_by(&mut input, |a, b| b.cmp(a));
+        assert_eq!(input, (0..100).rev().collect::<Vec<i32>>());
+    }
+
+    #[test]
+    fn intro_by_key_field() {
+        let mut input = vec![(1, 'c'), (2, 'a'), (3, 'b')];
+        intro_by_key(&mut input, |e| e.1);
+        assert_eq!(input, vec![(2, 'a'), (3, 'b'), (1, 'c')]);
+    }
+}
@@ -0,0 +1,166 @@
+use std::cmp::Ordering;
+use crate::sort::partial_compare;
+
+// Comparator of plain `kmerge`.
+type Compare<T> = fn(&T, &T) -> Ordering;
+
+/// Lazily merges already sorted iterators into a single sorted iterator. Elements comparing equal
+/// are yielded in the order of their source iterators, so the merge is stable.
+/// Each step takes O(logk) for k iterators.
+pub fn kmerge<I, J>(iters: I) -> KMerge<J::IntoIter, Compare<J::Item>>
+    where I: IntoIterator<Item = J>, J: IntoIterator, J::Item: PartialOrd {
+    kmerge_by(iters, partial_compare)
+}
+
+/// K-way merge of iterators sorted according to the comparator function.
+pub fn kmerge_by<I, J, F>(iters: I, compare: F) -> KMerge<J::IntoIter, F>
+    where I: IntoIterator<Item = J>, J: IntoIterator, F: FnMut(&J::Item, &J::Item) -> Ordering {
+    let mut m = KMerge {
+        heap: Vec::new(),
+        compare,
+    };
+    for (source, it) in iters.into_iter().enumerate() {
+        let mut iter = it.into_iter();
+        if let Some(item) = iter.next() {
+            m.heap.push(Head { item, source, iter });
+            let last = m.heap.len()-1;
+            m.swim(last);
+        }
+    }
+    m
+}
+
+/// Iterator returned by `kmerge` and `kmerge_by`.
+pub struct KMerge<I, F> where I: Iterator {
+    // min-oriented heap of the current heads, ordered by item and then by source index
+    heap: Vec<Head<I>>,
+    compare: F,
+}
+
+// Current head of a source iterator.
+struct Head<I> where I: Iterator {
+    item: I::Item,
+    source: usize,
+    iter: I,
+}
+
+impl<I, F> KMerge<I, F> where I: Iterator, F: FnMut(&I::Item, &I::Item) -> Ordering {
+    fn less(&mut self, i: usize, j: usize) -> bool {
+        let (a, b) = (&self.heap[i], &self.heap[j]);
+        match (self.compare)(&a.item, &b.item) {
+            Ordering::Equal => a.source < b.source,
+            o => o == Ordering::Less,
+        }
+    }
+
+    fn swim(&mut self, i: usize) {
+        let mut k = i;
+        while k > 0 {
+            let p = (k-1)/2;
+            if !self.less(k, p) {
+                break;
+            }
+            self.heap.swap(k, p);
+            k = p;
+        }
+    }
+
+    fn sink(&mut self, i: usize) {
+        let n = self.heap.len();
+        let mut k = i;
+        while 2*k+1 < n {
+            let mut c = 2*k+1;
+            if c+1 < n && self.less(c+1, c) {
+                // choosing minimum of two children
+                c += 1;
+            }
+            if !self.less(c, k) {
+                break;
+            }
+            self.heap.swap(k, c);
+            k = c;
+        }
+    }
+}
+
+impl<I, F> Iterator for KMerge<I, F> where I: Iterator, F: FnMut(&I::Item, &I::Item) -> Ordering {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.heap.is_empty() {
+            return None;
+        }
+
+        let item = match self.heap[0].iter.next() {
+            Some(next) => std::mem::replace(&mut self.heap[0].item, next),
+            None => self.heap.swap_remove(0).item,
+        };
+        if !self.heap.is_empty() {
+            self.sink(0);
+        }
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.heap.iter().fold((0, Some(0)), |(lo, hi), h| {
+            let (l, u) = h.iter.size_hint();
+            (lo.saturating_add(l).saturating_add(1), hi.and_then(|hi| u.and_then(|u| u.checked_add(1)).and_then(|u| hi.checked_add(u))))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kmerge_empty() {
+        let iters: Vec<Vec<i32>> = vec![];
+        assert_eq!(kmerge(iters).count(), 0);
+
+        let iters: Vec<Vec<i32>> = vec![vec![], vec![]];
+        assert_eq!(kmerge(iters).count(), 0);
+    }
+
+    #[test]
+    fn kmerge_sorted_sources() {
+        let iters = vec![vec![1, 4, 7], vec![2, 5, 8, 9], vec![], vec![0, 3, 6]];
+        let res: Vec<i32> = kmerge(iters).collect();
+        assert_eq!(res, (0..10).collect::<Vec<i32>>());
+    }
+
+    #[test]
+    fn kmerge_stable_by_source() {
+        let iters = vec![
+            vec![(1, 'a'), (2, 'a')],
+            vec![(1, 'b'), (2, 'b')],
+            vec![(1, 'c')],
+        ];
+        let res: Vec<(i32, char)> = kmerge_by(iters, |a, b| a.0.cmp(&b.0)).collect();
+        assert_eq!(res, vec![(1, 'a'), (1, 'b'), (1, 'c'), (2, 'a'), (2, 'b')]);
+    }
+
+    #[test]
+    fn kmerge_lazy_infinite_sources() {
+        let evens = (0..).step_by(2);
+        let odds = (1..).step_by(2);
+        let res: Vec<u64> = kmerge(vec![evens, odds]).take(6).collect();
+        assert_eq!(res, vec![0, 1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn kmerge_by_desc() {
+        let iters = vec![vec![9, 5, 1], vec![8, 6], vec![7]];
+        let res: Vec<i32> = kmerge_by(iters, |a: &i32, b: &i32| b.cmp(a)).collect();
+        assert_eq!(res, vec![9, 8, 7, 6, 5, 1]);
+    }
+
+    #[test]
+    fn kmerge_size_hint() {
+        let iters = vec![vec![1, 2], vec![3]];
+        assert_eq!(kmerge(iters).size_hint(), (3, Some(3)));
+
+        // the source left after taking its head reports exactly usize::MAX elements
+        assert_eq!(kmerge(vec![0..=usize::MAX]).size_hint(), (usize::MAX, None));
+    }
+}
@@ -1,52 +1,152 @@
-/// Merge sort: best and worst run time complexity is O(n*logn), space - O(n)
-pub fn merge<T>(list: &mut [T]) where T: std::cmp::PartialOrd + Copy {
-    sort_internal(list, 0, list.len()-1);
+use std::cmp::Ordering;
+use std::mem::MaybeUninit;
+use std::ptr;
+use crate::sort::partial_compare;
+use crate::sort::probe::{written, NoProbe, Probe};
+
+/// Merge sort: best and worst run time complexity is O(n*logn), space - O(n).
+/// Stable, allocates a single auxiliary buffer per call.
+pub fn merge<T>(list: &mut [T]) where T: PartialOrd {
+    merge_by(list, partial_compare);
+}
+
+/// Merge sort with a comparator function.
+pub fn merge_by<T, F>(list: &mut [T], compare: F) where F: FnMut(&T, &T) -> Ordering {
+    let mut buf = Vec::new();
+    merge_by_with_buffer(list, &mut buf, compare);
+}
+
+/// Merge sort with a key extraction function.
+pub fn merge_by_key<T, K, F>(list: &mut [T], mut key: F) where F: FnMut(&T) -> K, K: PartialOrd {
+    merge_by(list, |a, b| partial_compare(&key(a), &key(b)));
+}
+
+/// Merge sort using a caller-provided scratch buffer. Only the spare capacity of the buffer is used,
+/// its elements are left untouched. It is only grown when it has room for less than half of the list,
+/// so reusing it across calls avoids allocations.
+pub fn merge_with_buffer<T>(list: &mut [T], buf: &mut Vec<T>) where T: PartialOrd {
+    merge_by_with_buffer(list, buf, partial_compare);
+}
+
+/// Merge sort with a comparator function using a caller-provided scratch buffer.
+pub fn merge_by_with_buffer<T, F>(list: &mut [T], buf: &mut Vec<T>, compare: F)
+    where F: FnMut(&T, &T) -> Ordering {
+    merge_probed(list, buf, compare, &NoProbe);
+}
+
+pub(crate) fn merge_probed<T, F, P>(list: &mut [T], buf: &mut Vec<T>, mut compare: F, probe: &P)
+    where F: FnMut(&T, &T) -> Ordering, P: Probe<T> + ?Sized {
+    let need = list.len().div_ceil(2);
+    sort_internal(list, scratch(buf, need), &mut compare, probe);
+}
+
+// Spare capacity of the scratch buffer with room for `need` elements. Elements are only moved in and out
+// of it bitwise, so the length of the vector is left as it is and nothing is cloned or dropped in it.
+pub(crate) fn scratch<T>(buf: &mut Vec<T>, need: usize) -> &mut [MaybeUninit<T>] {
+    buf.reserve(need);
+    &mut buf.spare_capacity_mut()[..need]
 }
 
-fn sort_internal<T>(list: &mut [T], l: usize, r: usize) where T: std::cmp::PartialOrd + Copy {
-    if l >= r {
+// Sorts the list, buf must hold at least half of the list.
+pub(crate) fn sort_internal<T, F, P>(list: &mut [T], buf: &mut [MaybeUninit<T>], compare: &mut F, probe: &P)
+    where F: FnMut(&T, &T) -> Ordering, P: Probe<T> + ?Sized {
+    if list.len() < 2 {
         return;
     }
 
-    let mid = (l+r)/2;
-    sort_internal(list, l, mid);
-    sort_internal(list, mid+1, r);
-    merge_internal(list, l, mid, r);
+    probe.enter();
+    let mid = list.len()/2;
+    sort_internal(&mut list[..mid], buf, compare, probe);
+    sort_internal(&mut list[mid..], buf, compare, probe);
+    merge_internal(list, mid, buf, compare, probe);
+    probe.leave();
 }
 
-fn merge_internal<T>(list: &mut [T], l: usize, mid: usize, r: usize) where T: std::cmp::PartialOrd + Copy {
-    let mut copy = Vec::new();
-    for i in l..r+1 {
-        copy.push(list[i]);
-    }
-
-    let mid = mid-l;
-    let r = r-l;
-    let mut i = 0;
-    let mut j = mid+1;
-    let mut k = l;
-    while i <= mid && j <= r {
-        if copy[i] < copy[j] {
-            list[k] = copy[i];
-            i += 1;
+// Merges sorted runs list[..mid] and list[mid..], buf must hold at least mid elements.
+pub(crate) fn merge_internal<T, F, P>(list: &mut [T], mid: usize, buf: &mut [MaybeUninit<T>], compare: &mut F, probe: &P)
+    where F: FnMut(&T, &T) -> Ordering, P: Probe<T> + ?Sized {
+    if mid == 0 || mid == list.len() || compare(&list[mid], &list[mid-1]) != Ordering::Less {
+        // already in order
+        return;
+    }
+
+    let mut hole = MergeHole::new(list, &mut buf[..mid], probe);
+    while !hole.left().is_empty() && !hole.right().is_empty() {
+        // taking from the right run only if strictly less keeps the sort stable
+        if compare(&hole.right()[0], &hole.left()[0]) == Ordering::Less {
+            hole.take_right(1);
         } else {
-            list[k] = copy[j];
-            j += 1;
+            hole.take_left(1);
         }
-        k += 1;
+    }
+    // dropping the hole moves the rest of the left run back
+}
+
+// Left run moved out to the buffer while merging. The gap list[k..k+buf.len()-i] always has room for buf[i..],
+// dropping the hole (also when a comparator panics) moves the remaining elements back into the list.
+pub(crate) struct MergeHole<'a, T, P> where P: Probe<T> + ?Sized {
+    list: &'a mut [T],
+    buf: &'a mut [MaybeUninit<T>],
+    i: usize,
+    k: usize,
+    probe: &'a P,
+}
+
+impl<'a, T, P> MergeHole<'a, T, P> where P: Probe<T> + ?Sized {
+    // Moves the left run list[..buf.len()] out to the buffer.
+    pub(crate) fn new(list: &'a mut [T], buf: &'a mut [MaybeUninit<T>], probe: &'a P) -> Self {
+        assert!(buf.len() <= list.len());
+        unsafe {
+            ptr::copy_nonoverlapping(list.as_ptr(), buf.as_mut_ptr() as *mut T, buf.len());
+        }
+        MergeHole { list, buf, i: 0, k: 0, probe }
+    }
+
+    // Elements of the left run not merged yet.
+    pub(crate) fn left(&self) -> &[T] {
+        // buf[i..] holds the elements moved out of the list
+        unsafe { &*(&self.buf[self.i..] as *const [MaybeUninit<T>] as *const [T]) }
     }
 
-    // handling tail
-    while i <= mid {
-        list[k] = copy[i];
-        i += 1;
-        k += 1;
+    // Elements of the right run not merged yet.
+    pub(crate) fn right(&self) -> &[T] {
+        &self.list[self.k + self.buf.len() - self.i..]
     }
 
-    while j <= r {
-        list[k] = copy[j];
-        j += 1;
-        k += 1;
+    // Moves the next `count` elements of the left run into the gap.
+    pub(crate) fn take_left(&mut self, count: usize) {
+        let (i, k) = (self.i, self.k);
+        let src = &self.buf[i..i+count];
+        let dst = &mut self.list[k..k+count];
+        unsafe {
+            ptr::copy_nonoverlapping(src.as_ptr() as *const T, dst.as_mut_ptr(), count);
+        }
+        self.i += count;
+        self.k += count;
+        written(&self.list[k..k+count], self.probe);
+    }
+
+    // Moves the next `count` elements of the right run to the front of the gap.
+    pub(crate) fn take_right(&mut self, count: usize) {
+        let (j, k) = (self.k + self.buf.len() - self.i, self.k);
+        assert!(j + count <= self.list.len());
+        unsafe {
+            let p = self.list.as_mut_ptr();
+            ptr::copy(p.add(j), p.add(k), count);
+        }
+        self.k += count;
+        written(&self.list[k..k+count], self.probe);
+    }
+}
+
+impl<'a, T, P> Drop for MergeHole<'a, T, P> where P: Probe<T> + ?Sized {
+    fn drop(&mut self) {
+        let (i, k) = (self.i, self.k);
+        let rest = self.buf.len() - i;
+        unsafe {
+            ptr::copy_nonoverlapping(self.buf[i..].as_ptr() as *const T, self.list[k..k+rest].as_mut_ptr(), rest);
+        }
+        written(&self.list[k..k+rest], self.probe);
     }
 }
 
@@ -89,5 +189,71 @@ mod test {
         let mut input = vec![5, 4, 3, 2, 1];
         merge(&mut input);
         check_order(&input);
-    }    
+    }
+
+    #[test]
+    fn merge_by_desc() {
+        let mut input = vec![1, 3, 2, 5, 4];
+        merge_by(&mut input, |a, b| b.cmp(a));
+        assert_eq!(input, vec![5, 4, 3, 2, 1]);
+    }
+
+    #[test]
+    fn merge_by_key_field() {
+        let mut input = vec![(1, 'c'), (2, 'a'), (3, 'b')];
+        merge_by_key(&mut input, |e| e.1);
+        assert_eq!(input, vec![(2, 'a'), (3, 'b'), (1, 'c')]);
+    }
+
+    #[test]
+    fn merge_stable() {
+        let mut input = vec![(2, 'a'), (1, 'b'), (2, 'c'), (1, 'd'), (2, 'e'), (0, 'f')];
+        merge_by_key(&mut input, |e| e.0);
+        assert_eq!(input, vec![(0, 'f'), (1, 'b'), (1, 'd'), (2, 'a'), (2, 'c'), (2, 'e')]);
+    }
+
+    #[test]
+    fn merge_strings() {
+        let mut input: Vec<String> = vec!["pear", "apple", "fig", "banana", "apple"]
+            .into_iter().map(String::from).collect();
+        merge(&mut input);
+        assert_eq!(input, vec!["apple", "apple", "banana", "fig", "pear"]);
+    }
+
+    #[test]
+    fn merge_reuse_buffer() {
+        let mut buf = vec![String::from("kept")];
+        let mut allocation = None;
+        for n in (1..50).rev() {
+            let mut input: Vec<String> = (0..n).map(|i| ((i * 17) % 11).to_string()).collect();
+            merge_with_buffer(&mut input, &mut buf);
+            check_order(&input);
+            // sized by the first, longest input only
+            assert_eq!(*allocation.get_or_insert(buf.as_ptr()), buf.as_ptr());
+        }
+        assert!(buf.capacity() >= 26);
+        assert_eq!(buf, vec!["kept"]);
+    }
+
+    #[test]
+    fn merge_no_clone() {
+        #[derive(Debug, PartialEq, PartialOrd)]
+        struct Record(u32, Box<u32>);
+
+        let mut input: Vec<Record> = (0..100).map(|i| Record((i * 7) % 10, Box::new(i))).collect();
+        merge_by_key(&mut input, |r| r.0);
+        check_order(&input);
+        assert!(input.windows(2).all(|w| w[0].0 < w[1].0 || w[0].1 < w[1].1));
+    }
+
+    #[test]
+    fn merge_empty_and_single() {
+        let mut input: Vec<i32> = vec![];
+        merge(&mut input);
+        assert!(input.is_empty());
+
+        let mut input = vec![1];
+        merge(&mut input);
+        assert_eq!(input, vec![1]);
+    }
 }
@@ -0,0 +1,86 @@
+use std::cmp::Ordering;
+use crate::sort::partial_compare;
+use crate::sort::merge::{merge_internal, scratch};
+use crate::sort::probe::{NoProbe, Probe};
+
+/// Bottom-up merge sort: merges runs of width 1, 2, 4, ... without recursion.
+/// Run time O(n*logn), space - O(n), stable.
+pub fn merge_bottom_up<T>(list: &mut [T]) where T: PartialOrd {
+    merge_bottom_up_by(list, partial_compare);
+}
+
+/// Bottom-up merge sort with a comparator function.
+pub fn merge_bottom_up_by<T, F>(list: &mut [T], compare: F) where F: FnMut(&T, &T) -> Ordering {
+    merge_bottom_up_probed(list, compare, &NoProbe);
+}
+
+/// Bottom-up merge sort with a key extraction function.
+pub fn merge_bottom_up_by_key<T, K, F>(list: &mut [T], mut key: F) where F: FnMut(&T) -> K, K: PartialOrd {
+    merge_bottom_up_by(list, |a, b| partial_compare(&key(a), &key(b)));
+}
+
+pub(crate) fn merge_bottom_up_probed<T, F, P>(list: &mut [T], mut compare: F, probe: &P)
+    where F: FnMut(&T, &T) -> Ordering, P: Probe<T> + ?Sized {
+    let n = list.len();
+    let mut buf = Vec::new();
+    let mut width = 1;
+    while width < n {
+        let mut lo = 0;
+        while lo + width < n {
+            let hi = n.min(lo + 2*width);
+            merge_internal(&mut list[lo..hi], width, scratch(&mut buf, width), &mut compare, probe);
+            lo = hi;
+        }
+        width *= 2;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check_order<T>(list: &[T]) where T: PartialOrd + std::fmt::Debug {
+        let mut prev: &T = &list[0];
+        for a in list.iter() {
+            assert!(a >= prev, "non-decreasing order violation: {:?}", list);
+            prev = a;
+        }
+    }
+
+    #[test]
+    fn merge_bottom_up_two_elements() {
+        let mut input = vec![2, 1];
+        merge_bottom_up(&mut input);
+        check_order(&input);
+    }
+
+    #[test]
+    fn merge_bottom_up_basic_sort() {
+        let mut input = vec![1, 3, 2, 5, 4];
+        merge_bottom_up(&mut input);
+        check_order(&input);
+    }
+
+    #[test]
+    fn merge_bottom_up_desc_sort() {
+        let mut input = vec![5, 4, 3, 2, 1];
+        merge_bottom_up(&mut input);
+        check_order(&input);
+    }
+
+    #[test]
+    fn merge_bottom_up_odd_sizes() {
+        for n in 1..40 {
+            let mut input: Vec<i32> = (0..n).map(|i| (i * 13) % 7).collect();
+            merge_bottom_up(&mut input);
+            check_order(&input);
+        }
+    }
+
+    #[test]
+    fn merge_bottom_up_stable() {
+        let mut input = vec![(2, 'a'), (1, 'b'), (2, 'c'), (1, 'd'), (2, 'e'), (0, 'f')];
+        merge_bottom_up_by_key(&mut input, |e| e.0);
+        assert_eq!(input, vec![(0, 'f'), (1, 'b'), (1, 'd'), (2, 'a'), (2, 'c'), (2, 'e')]);
+    }
+}
@@ -0,0 +1,140 @@
+use std::cmp::Ordering;
+use crate::sort::partial_compare;
+use crate::sort::merge::{merge_internal, scratch};
+use crate::sort::probe::{reverse, NoProbe, Probe};
+
+/// Natural merge sort: detects existing ascending and descending runs and merges only those.
+/// Run time O(n*logn) worst case, O(n) for presorted slices, space - O(n), stable.
+pub fn merge_natural<T>(list: &mut [T]) where T: PartialOrd {
+    merge_natural_by(list, partial_compare);
+}
+
+/// Natural merge sort with a comparator function.
+pub fn merge_natural_by<T, F>(list: &mut [T], compare: F) where F: FnMut(&T, &T) -> Ordering {
+    merge_natural_probed(list, compare, &NoProbe);
+}
+
+/// Natural merge sort with a key extraction function.
+pub fn merge_natural_by_key<T, K, F>(list: &mut [T], mut key: F) where F: FnMut(&T) -> K, K: PartialOrd {
+    merge_natural_by(list, |a, b| partial_compare(&key(a), &key(b)));
+}
+
+pub(crate) fn merge_natural_probed<T, F, P>(list: &mut [T], mut compare: F, probe: &P)
+    where F: FnMut(&T, &T) -> Ordering, P: Probe<T> + ?Sized {
+    // run i spans list[runs[i]..runs[i+1]]
+    let mut runs = find_runs(list, &mut compare, probe);
+    let mut buf = Vec::new();
+    while runs.len() > 2 {
+        let mut merged = vec![0];
+        let mut i = 0;
+        while i + 2 < runs.len() {
+            let (lo, mid, hi) = (runs[i], runs[i+1], runs[i+2]);
+            merge_internal(&mut list[lo..hi], mid - lo, scratch(&mut buf, mid - lo), &mut compare, probe);
+            merged.push(hi);
+            i += 2;
+        }
+        if i + 1 < runs.len() {
+            // odd run out is carried over to the next pass
+            merged.push(runs[i+1]);
+        }
+        runs = merged;
+    }
+}
+
+// Splits the list into non-decreasing runs, strictly descending runs are reversed in place.
+// Returns run boundaries including 0 and list.len().
+fn find_runs<T, F, P>(list: &mut [T], compare: &mut F, probe: &P) -> Vec<usize>
+    where F: FnMut(&T, &T) -> Ordering, P: Probe<T> + ?Sized {
+    let n = list.len();
+    let mut runs = vec![0];
+    let mut lo = 0;
+    while lo < n {
+        lo += count_run(&mut list[lo..], compare, probe);
+        runs.push(lo);
+    }
+    runs
+}
+
+// Length of the non-decreasing run at the start of a non-empty list, a strictly descending run is reversed.
+pub(crate) fn count_run<T, F, P>(list: &mut [T], compare: &mut F, probe: &P) -> usize
+    where F: FnMut(&T, &T) -> Ordering, P: Probe<T> + ?Sized {
+    let n = list.len();
+    let mut hi = 1;
+    if hi < n && compare(&list[hi], &list[hi-1]) == Ordering::Less {
+        // only strictly descending runs are reversed to keep the sort stable
+        while hi < n && compare(&list[hi], &list[hi-1]) == Ordering::Less {
+            hi += 1;
+        }
+        reverse(&mut list[..hi], probe);
+    } else {
+        while hi < n && compare(&list[hi], &list[hi-1]) != Ordering::Less {
+            hi += 1;
+        }
+    }
+    hi
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check_order<T>(list: &[T]) where T: PartialOrd + std::fmt::Debug {
+        let mut prev: &T = &list[0];
+        for a in list.iter() {
+            assert!(a >= prev, "non-decreasing order violation: {:?}", list);
+            prev = a;
+        }
+    }
+
+    #[test]
+    fn merge_natural_two_elements() {
+        let mut input = vec![2, 1];
+        merge_natural(&mut input);
+        check_order(&input);
+    }
+
+    #[test]
+    fn merge_natural_basic_sort() {
+        let mut input = vec![1, 3, 2, 5, 4];
+        merge_natural(&mut input);
+        check_order(&input);
+    }
+
+    #[test]
+    fn merge_natural_asc_sort() {
+        let mut input = vec![1, 2, 3, 4, 5];
+        merge_natural(&mut input);
+        check_order(&input);
+    }
+
+    #[test]
+    fn merge_natural_desc_sort() {
+        let mut input = vec![5, 4, 3, 2, 1];
+        merge_natural(&mut input);
+        check_order(&input);
+    }
+
+    #[test]
+    fn merge_natural_runs() {
+        let mut list = vec![1, 2, 3, 9, 8, 7, 4, 4, 5, 0];
+        let runs = find_runs(&mut list, &mut |a: &i32, b: &i32| a.cmp(b), &NoProbe);
+        assert_eq!(runs, vec![0, 4, 7, 9, 10]);
+        assert_eq!(list, vec![1, 2, 3, 9, 4, 7, 8, 4, 5, 0]);
+    }
+
+    #[test]
+    fn merge_natural_stable() {
+        let mut input = vec![(2, 'a'), (1, 'b'), (2, 'c'), (1, 'd'), (2, 'e'), (0, 'f')];
+        merge_natural_by_key(&mut input, |e| e.0);
+        assert_eq!(input, vec![(0, 'f'), (1, 'b'), (1, 'd'), (2, 'a'), (2, 'c'), (2, 'e')]);
+    }
+
+    #[test]
+    fn merge_natural_odd_sizes() {
+        for n in 1..40 {
+            let mut input: Vec<i32> = (0..n).map(|i| (i * 13) % 7).collect();
+            merge_natural(&mut input);
+            check_order(&input);
+        }
+    }
+}
@@ -0,0 +1,184 @@
+use std::cmp::Ordering;
+use std::mem::MaybeUninit;
+use std::thread;
+use crate::sort::partial_compare;
+use crate::sort::merge::{merge_internal, sort_internal};
+use crate::sort::probe::NoProbe;
+
+// Slices of this size or smaller are sorted and merged on the current thread.
+const CUTOFF: usize = 4096;
+
+/// Parallel merge sort on scoped threads, one per available core: run time O(n*logn/p) for p threads,
+/// space - O(n). Stable, the result is identical to `merge`.
+pub fn par_merge<T>(list: &mut [T]) where T: PartialOrd + Send {
+    par_merge_by(list, partial_compare);
+}
+
+/// Parallel merge sort with a comparator function shared by all threads.
+pub fn par_merge_by<T, F>(list: &mut [T], compare: F) where T: Send, F: Fn(&T, &T) -> Ordering + Sync {
+    let threads = thread::available_parallelism().map_or(1, |n| n.get());
+    par_merge_by_with_threads(list, threads, compare);
+}
+
+/// Parallel merge sort with a key extraction function.
+pub fn par_merge_by_key<T, K, F>(list: &mut [T], key: F) where T: Send, F: Fn(&T) -> K + Sync, K: PartialOrd {
+    par_merge_by(list, |a, b| partial_compare(&key(a), &key(b)));
+}
+
+/// Parallel merge sort using at most `threads` threads, including the calling one.
+pub fn par_merge_with_threads<T>(list: &mut [T], threads: usize) where T: PartialOrd + Send {
+    par_merge_by_with_threads(list, threads, partial_compare);
+}
+
+/// Parallel merge sort with a comparator function using at most `threads` threads.
+/// If the comparator panics, the panic is propagated once all threads are finished.
+pub fn par_merge_by_with_threads<T, F>(list: &mut [T], threads: usize, compare: F)
+    where T: Send, F: Fn(&T, &T) -> Ordering + Sync {
+    assert!(threads > 0, "thread count must be positive");
+    if list.len() < 2 {
+        return;
+    }
+
+    // buf[lo..hi] is the scratch space of list[lo..hi], so halves can be handed to separate threads
+    let mut buf = Vec::with_capacity(list.len());
+    sort_parallel(list, buf.spare_capacity_mut(), threads, &compare);
+}
+
+fn sort_parallel<T, F>(list: &mut [T], buf: &mut [MaybeUninit<T>], threads: usize, compare: &F)
+    where T: Send, F: Fn(&T, &T) -> Ordering + Sync {
+    if threads == 1 || list.len() <= CUTOFF {
+        sort_internal(list, buf, &mut &*compare, &NoProbe);
+        return;
+    }
+
+    let mid = list.len()/2;
+    {
+        let (left, right) = list.split_at_mut(mid);
+        let (left_buf, right_buf) = buf.split_at_mut(mid);
+        thread::scope(|s| {
+            s.spawn(|| sort_parallel(left, left_buf, threads/2, compare));
+            sort_parallel(right, right_buf, threads - threads/2, compare);
+        });
+    }
+    merge_parallel(list, mid, buf, threads, compare);
+}
+
+// Merges sorted runs list[..mid] and list[mid..]. Both runs are split by the median of the longer one,
+// the inner parts are swapped with a rotation and the two halves are merged independently.
+fn merge_parallel<T, F>(list: &mut [T], mid: usize, buf: &mut [MaybeUninit<T>], threads: usize, compare: &F)
+    where T: Send, F: Fn(&T, &T) -> Ordering + Sync {
+    let len = list.len();
+    if threads == 1 || len <= CUTOFF {
+        merge_internal(list, mid, buf, &mut &*compare, &NoProbe);
+        return;
+    }
+    if mid == 0 || mid == len || compare(&list[mid], &list[mid-1]) != Ordering::Less {
+        // already in order
+        return;
+    }
+
+    // list[..i] and list[mid..mid+j] precede list[i..mid] and list[mid+j..] in the merged list,
+    // equal elements of the left run go first to keep the sort stable
+    let (a, b) = list.split_at(mid);
+    let (i, j) = if a.len() >= b.len() {
+        let i = a.len()/2;
+        (i, b.partition_point(|e| compare(e, &a[i]) == Ordering::Less))
+    } else {
+        let j = b.len()/2;
+        (a.partition_point(|e| compare(e, &b[j]) != Ordering::Greater), j)
+    };
+    list[i..mid+j].rotate_left(mid-i);
+
+    let (left, right) = list.split_at_mut(i+j);
+    let (left_buf, right_buf) = buf.split_at_mut(i+j);
+    thread::scope(|s| {
+        s.spawn(|| merge_parallel(left, i, left_buf, threads/2, compare));
+        merge_parallel(right, mid-i, right_buf, threads - threads/2, compare);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Pseudo-random keys with many duplicates, tagged with their original position.
+    fn input(n: usize) -> Vec<(u32, usize)> {
+        (0..n).map(|i| (((i as u64 * 2654435761) % 1000) as u32, i)).collect()
+    }
+
+    #[test]
+    fn par_merge_empty_and_single() {
+        let mut list: Vec<i32> = vec![];
+        par_merge(&mut list);
+        assert!(list.is_empty());
+
+        let mut list = vec![1];
+        par_merge_with_threads(&mut list, 4);
+        assert_eq!(list, vec![1]);
+    }
+
+    #[test]
+    fn par_merge_matches_stable_sort() {
+        for &n in [100, 10000, 50000].iter() {
+            let original = input(n);
+            let mut expected = original.clone();
+            expected.sort_by_key(|e| e.0);
+            for &threads in [1, 2, 3, 8].iter() {
+                let mut list = original.clone();
+                par_merge_by_with_threads(&mut list, threads, |a, b| a.0.cmp(&b.0));
+                assert_eq!(list, expected, "n = {}, threads = {}", n, threads);
+            }
+        }
+    }
+
+    #[test]
+    fn par_merge_presorted() {
+        let mut asc: Vec<i32> = (0..30000).collect();
+        par_merge_with_threads(&mut asc, 4);
+        assert_eq!(asc, (0..30000).collect::<Vec<i32>>());
+
+        let mut desc: Vec<i32> = (0..30000).rev().collect();
+        par_merge_with_threads(&mut desc, 4);
+        assert_eq!(desc, (0..30000).collect::<Vec<i32>>());
+    }
+
+    #[test]
+    fn par_merge_by_key_strings() {
+        let mut list: Vec<String> = (0..20000).map(|i| format!("{}", (i * 7919) % 20011)).collect();
+        let mut expected = list.clone();
+        expected.sort_by_key(|s| s.len());
+        par_merge_by_key(&mut list, |s| s.len());
+        assert_eq!(list, expected);
+    }
+
+    #[test]
+    fn par_merge_panic_keeps_permutation() {
+        use std::panic::{self, AssertUnwindSafe};
+        use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+
+        let original = input(40000);
+        let mut list = original.clone();
+        let count = AtomicUsize::new(0);
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            par_merge_by_with_threads(&mut list, 4, |a, b| {
+                if count.fetch_add(1, AtomicOrdering::Relaxed) == 300000 {
+                    panic!("comparator panic");
+                }
+                a.cmp(b)
+            });
+        }));
+        assert!(result.is_err());
+
+        let mut expected = original;
+        expected.sort();
+        list.sort();
+        assert_eq!(list, expected);
+    }
+
+    #[test]
+    #[should_panic]
+    fn par_merge_zero_threads() {
+        let mut list = vec![2, 1];
+        par_merge_with_threads(&mut list, 0);
+    }
+}
@@ -0,0 +1,160 @@
+use std::cmp::Ordering;
+use std::thread;
+use crate::sort::partial_compare;
+use crate::sort::pdq::{choose_pivot, pdq_by};
+use crate::sort::probe::NoProbe;
+use crate::sort::quick_3_way::partition_around_first;
+
+// Slices of this size or smaller are sorted on the current thread.
+const CUTOFF: usize = 4096;
+
+/// Parallel quick sort on scoped threads, one per available core. Partitions are sorted on separate
+/// threads until the threads are used up, then finished with `pdq`.
+/// Run time O(n*logn/p) average case for p threads, O(n*logn) worst case, space - O(logn). Not stable.
+pub fn par_quick<T>(list: &mut [T]) where T: PartialOrd + Send {
+    par_quick_by(list, partial_compare);
+}
+
+/// Parallel quick sort with a comparator function shared by all threads.
+pub fn par_quick_by<T, F>(list: &mut [T], compare: F) where T: Send, F: Fn(&T, &T) -> Ordering + Sync {
+    let threads = thread::available_parallelism().map_or(1, |n| n.get());
+    par_quick_by_with_threads(list, threads, compare);
+}
+
+/// Parallel quick sort with a key extraction function.
+pub fn par_quick_by_key<T, K, F>(list: &mut [T], key: F) where T: Send, F: Fn(&T) -> K + Sync, K: PartialOrd {
+    par_quick_by(list, |a, b| partial_compare(&key(a), &key(b)));
+}
+
+/// Parallel quick sort using at most `threads` threads, including the calling one.
+pub fn par_quick_with_threads<T>(list: &mut [T], threads: usize) where T: PartialOrd + Send {
+    par_quick_by_with_threads(list, threads, partial_compare);
+}
+
+/// Parallel quick sort with a comparator function using at most `threads` threads.
+/// If the comparator panics, the panic is propagated once all threads are finished.
+pub fn par_quick_by_with_threads<T, F>(list: &mut [T], threads: usize, compare: F)
+    where T: Send, F: Fn(&T, &T) -> Ordering + Sync {
+    assert!(threads > 0, "thread count must be positive");
+    sort_parallel(list, threads, &compare);
+}
+
+fn sort_parallel<T, F>(list: &mut [T], threads: usize, compare: &F) where T: Send, F: Fn(&T, &T) -> Ordering + Sync {
+    if threads == 1 || list.len() <= CUTOFF {
+        pdq_by(list, compare);
+        return;
+    }
+
+    let mut cmp = compare;
+    let (pivot, _) = choose_pivot(list, &mut cmp, &NoProbe);
+    list.swap(0, pivot);
+    let (lt, gt) = partition_around_first(list, &mut cmp, &NoProbe);
+    let (less, rest) = list.split_at_mut(lt);
+    let greater = &mut rest[gt-lt..];
+
+    let total = less.len() + greater.len();
+    if total == 0 {
+        // all elements equal the pivot
+        return;
+    }
+
+    // threads are shared in proportion to the part sizes, each part gets at least one
+    let less_threads = (threads * less.len() / total).clamp(1, threads - 1);
+    thread::scope(|s| {
+        s.spawn(|| sort_parallel(less, less_threads, compare));
+        sort_parallel(greater, threads - less_threads, compare);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate test;
+
+    use super::*;
+    use rand::Rng;
+    use test::Bencher;
+
+    fn check_order<T>(list: &[T]) where T: PartialOrd + std::fmt::Debug {
+        let mut prev: &T = &list[0];
+        for a in list.iter() {
+            assert!(a >= prev, "non-decreasing order violation: {:?}", list);
+            prev = a;
+        }
+    }
+
+    fn random_u64(n: usize) -> Vec<u64> {
+        let mut rng = rand::thread_rng();
+        (0..n).map(|_| rng.gen::<u64>()).collect()
+    }
+
+    #[test]
+    fn par_quick_empty_and_single() {
+        let mut list: Vec<i32> = vec![];
+        par_quick(&mut list);
+        assert!(list.is_empty());
+
+        let mut list = vec![1];
+        par_quick_with_threads(&mut list, 4);
+        assert_eq!(list, vec![1]);
+    }
+
+    #[test]
+    fn par_quick_thread_counts() {
+        let original: Vec<u64> = (0..50000u64).map(|i| i.wrapping_mul(0x9E37_79B9_7F4A_7C15)).collect();
+        let mut expected = original.clone();
+        expected.sort();
+        for &threads in [1, 2, 3, 8].iter() {
+            let mut list = original.clone();
+            par_quick_with_threads(&mut list, threads);
+            assert_eq!(list, expected, "threads = {}", threads);
+        }
+    }
+
+    #[test]
+    fn par_quick_patterns() {
+        let asc: Vec<i32> = (0..30000).collect();
+        let desc: Vec<i32> = (0..30000).rev().collect();
+        let few: Vec<i32> = (0..30000).map(|i| (i * 7919) % 3).collect();
+        let equal = vec![5; 30000];
+        for input in [asc, desc, few, equal].iter_mut() {
+            par_quick_with_threads(input, 4);
+            check_order(input);
+        }
+    }
+
+    #[test]
+    fn par_quick_by_desc_and_key() {
+        let mut list: Vec<i32> = (0..20000).collect();
+        par_quick_by(&mut list, |a, b| b.cmp(a));
+        assert_eq!(list, (0..20000).rev().collect::<Vec<i32>>());
+
+        let mut list: Vec<(i32, String)> = (0..20000).map(|i| ((i * 7919) % 20011, i.to_string())).collect();
+        par_quick_by_key(&mut list, |e| e.0);
+        assert!(list.windows(2).all(|w| w[0].0 <= w[1].0));
+    }
+
+    #[test]
+    #[should_panic]
+    fn par_quick_zero_threads() {
+        let mut list = vec![2, 1];
+        par_quick_with_threads(&mut list, 0);
+    }
+
+    #[bench]
+    fn bench_par_quick_100000_u64(b: &mut Bencher) {
+        let input = random_u64(100000);
+        b.iter(|| {
+            let mut list = input.clone();
+            par_quick(&mut list);
+        });
+    }
+
+    #[bench]
+    fn bench_pdq_100000_u64(b: &mut Bencher) {
+        let input = random_u64(100000);
+        b.iter(|| {
+            let mut list = input.clone();
+            crate::sort::pdq(&mut list);
+        });
+    }
+}
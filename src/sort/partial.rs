@@ -0,0 +1,162 @@
+use std::cmp::Ordering;
+use crate::sort::partial_compare;
+use crate::sort::heap::{heap_by, sink};
+use crate::sort::probe::NoProbe;
+
+/// Partial sort: puts the k smallest elements in order at the front of the list, the rest of the list
+/// is left in unspecified order. Run time O(n*logk), space - O(1).
+pub fn partial_sort<T>(list: &mut [T], k: usize) where T: PartialOrd {
+    partial_sort_by(list, k, partial_compare);
+}
+
+/// Partial sort with a comparator function.
+pub fn partial_sort_by<T, F>(list: &mut [T], k: usize, mut compare: F) where F: FnMut(&T, &T) -> Ordering {
+    let k = k.min(list.len());
+    if k == 0 {
+        return;
+    }
+
+    // list[..k] is a max-oriented heap of the k smallest elements seen so far
+    for i in (0..k/2).rev() {
+        sink(list, i, k, &mut compare, &NoProbe);
+    }
+    for i in k..list.len() {
+        if compare(&list[i], &list[0]) == Ordering::Less {
+            list.swap(0, i);
+            sink(list, 0, k, &mut compare, &NoProbe);
+        }
+    }
+
+    for n in (1..k).rev() {
+        list.swap(0, n);
+        sink(list, 0, n, &mut compare, &NoProbe);
+    }
+}
+
+/// Partial sort with a key extraction function.
+pub fn partial_sort_by_key<T, K, F>(list: &mut [T], k: usize, mut key: F) where F: FnMut(&T) -> K, K: PartialOrd {
+    partial_sort_by(list, k, |a, b| partial_compare(&key(a), &key(b)));
+}
+
+/// Returns the k largest elements of the iterator in non-increasing order. Keeps a bounded heap,
+/// so the iterator may be arbitrary long: run time O(n*logk), space - O(min(n, k)).
+pub fn top_k<T, I>(iter: I, k: usize) -> Vec<T> where T: PartialOrd, I: IntoIterator<Item = T> {
+    top_k_internal(iter, k, partial_compare)
+}
+
+/// Returns the k elements of the iterator with the largest keys in non-increasing key order.
+/// Elements with equal keys are returned in unspecified order.
+pub fn top_k_by_key<T, K, I, F>(iter: I, k: usize, mut key: F) -> Vec<T>
+    where K: PartialOrd, I: IntoIterator<Item = T>, F: FnMut(&T) -> K {
+    let keyed = iter.into_iter().map(|e| (key(&e), e));
+    top_k_internal(keyed, k, |a: &(K, T), b: &(K, T)| partial_compare(&a.0, &b.0))
+        .into_iter()
+        .map(|(_, e)| e)
+        .collect()
+}
+
+fn top_k_internal<T, I, F>(iter: I, k: usize, mut compare: F) -> Vec<T>
+    where I: IntoIterator<Item = T>, F: FnMut(&T, &T) -> Ordering {
+    if k == 0 {
+        return Vec::new();
+    }
+
+    // min-oriented heap under the reversed comparator, the top is the smallest of the k largest
+    // elements. It grows with the elements seen, so a large k allocates nothing up front.
+    let mut reversed = |a: &T, b: &T| compare(b, a);
+    let mut h: Vec<T> = Vec::new();
+    for e in iter {
+        if h.len() < k {
+            h.push(e);
+            if h.len() == k {
+                for i in (0..k/2).rev() {
+                    sink(&mut h, i, k, &mut reversed, &NoProbe);
+                }
+            }
+        } else if reversed(&e, &h[0]) == Ordering::Less {
+            h[0] = e;
+            sink(&mut h, 0, k, &mut reversed, &NoProbe);
+        }
+    }
+
+    heap_by(&mut h, reversed);
+    h
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check_order<T>(list: &[T]) where T: PartialOrd + std::fmt::Debug {
+        let mut prev: &T = &list[0];
+        for a in list.iter() {
+            assert!(a >= prev, "non-decreasing order violation: {:?}", list);
+            prev = a;
+        }
+    }
+
+    #[test]
+    fn partial_sort_front() {
+        let mut input: Vec<i32> = (0..100).map(|i| (i * 37) % 101).collect();
+        partial_sort(&mut input, 10);
+        check_order(&input[..10]);
+        for e in input[10..].iter() {
+            assert!(e >= &input[9]);
+        }
+
+        let mut expected: Vec<i32> = (0..100).map(|i| (i * 37) % 101).collect();
+        expected.sort();
+        assert_eq!(&input[..10], &expected[..10]);
+        input.sort();
+        assert_eq!(input, expected);
+    }
+
+    #[test]
+    fn partial_sort_bounds() {
+        let mut input = vec![3, 1, 2];
+        partial_sort(&mut input, 0);
+        assert_eq!(input, vec![3, 1, 2]);
+
+        partial_sort(&mut input, 5);
+        assert_eq!(input, vec![1, 2, 3]);
+
+        let mut empty: Vec<i32> = vec![];
+        partial_sort(&mut empty, 1);
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn partial_sort_by_key_desc() {
+        let mut input: Vec<String> = vec!["bb", "a", "dddd", "ccc"].into_iter().map(String::from).collect();
+        partial_sort_by_key(&mut input, 2, |s| std::cmp::Reverse(s.len()));
+        assert_eq!(&input[..2], &["dddd", "ccc"]);
+    }
+
+    #[test]
+    fn top_k_largest() {
+        let res = top_k((0..1_000_000i64).map(|i| (i * 7919) % 1_000_003), 5);
+        assert_eq!(res, vec![1_000_002, 1_000_001, 1_000_000, 999_999, 999_998]);
+    }
+
+    #[test]
+    fn top_k_short_iterator() {
+        assert_eq!(top_k(vec![2, 3, 1], 5), vec![3, 2, 1]);
+        assert_eq!(top_k(vec![2, 3, 1], 0), Vec::<i32>::new());
+        assert_eq!(top_k(vec![1, 2, 3], usize::MAX), vec![3, 2, 1]);
+        assert_eq!(top_k_by_key(vec!["a", "ccc", "bb"], usize::MAX, |s| s.len()), vec!["ccc", "bb", "a"]);
+    }
+
+    #[test]
+    fn top_k_not_copy() {
+        let words = (0..1000).map(|i| format!("{:03}", (i * 7) % 1000));
+        assert_eq!(top_k(words, 3), vec!["999", "998", "997"]);
+    }
+
+    #[test]
+    fn top_k_by_key_records() {
+        let rows: Vec<(String, u32)> = [("ann", 70), ("bob", 95), ("cid", 80), ("dan", 60), ("eve", 90)]
+            .iter().map(|r| (r.0.to_string(), r.1)).collect();
+        let res = top_k_by_key(rows, 3, |r| r.1);
+        assert_eq!(res, vec![(String::from("bob"), 95), (String::from("eve"), 90), (String::from("cid"), 80)]);
+    }
+}
@@ -0,0 +1,143 @@
+use std::cmp::Ordering;
+use crate::sort::partial_compare;
+use crate::sort::probe::{swap, NoProbe, Probe};
+
+/// Dutch national flag partitioning around a pivot value: O(n) run time, O(1) space.
+/// Returns `(lt, gt)` so that `list[..lt]` are less than the pivot, `list[lt..gt]` equal and
+/// `list[gt..]` greater.
+pub fn partition3<T>(list: &mut [T], pivot: &T) -> (usize, usize) where T: PartialOrd {
+    partition3_by(list, |e| partial_compare(e, pivot))
+}
+
+/// Dutch national flag partitioning by a function telling how an element relates to the pivot.
+/// Returns `(lt, gt)` boundaries of the elements for which it returned `Ordering::Equal`.
+pub fn partition3_by<T, F>(list: &mut [T], f: F) -> (usize, usize) where F: FnMut(&T) -> Ordering {
+    partition3_probed(list, f, &NoProbe)
+}
+
+pub(crate) fn partition3_probed<T, F, P>(list: &mut [T], mut f: F, probe: &P) -> (usize, usize)
+    where F: FnMut(&T) -> Ordering, P: Probe<T> + ?Sized {
+    // list[..l] are less, list[l..i] equal, list[g..] greater
+    let mut l = 0;
+    let mut i = 0;
+    let mut g = list.len();
+    while i < g {
+        match f(&list[i]) {
+            Ordering::Less => {
+                swap(list, l, i, probe);
+                l += 1;
+                i += 1;
+            }
+            Ordering::Greater => {
+                g -= 1;
+                swap(list, i, g, probe);
+            }
+            Ordering::Equal => {
+                i += 1;
+            }
+        }
+    }
+    (l, g)
+}
+
+/// Moves elements matching the predicate to the front of the list, O(n) run time, O(1) space.
+/// Returns the number of matching elements. The order within both groups is not preserved.
+pub fn partition_by<T, P>(list: &mut [T], mut pred: P) -> usize where P: FnMut(&T) -> bool {
+    let mut i = 0;
+    for j in 0..list.len() {
+        if pred(&list[j]) {
+            list.swap(i, j);
+            i += 1;
+        }
+    }
+    i
+}
+
+/// Stable in-place partitioning: moves elements matching the predicate to the front of the list
+/// keeping the relative order within both groups. Run time O(n*logn), space - O(logn), no allocations.
+/// Returns the number of matching elements.
+pub fn partition_stable_by<T, P>(list: &mut [T], mut pred: P) -> usize where P: FnMut(&T) -> bool {
+    stable_internal(list, &mut pred)
+}
+
+fn stable_internal<T, P>(list: &mut [T], pred: &mut P) -> usize where P: FnMut(&T) -> bool {
+    match list.len() {
+        0 => 0,
+        1 => pred(&list[0]) as usize,
+        n => {
+            let mid = n/2;
+            let l = stable_internal(&mut list[..mid], pred);
+            let r = stable_internal(&mut list[mid..], pred);
+            // [matching left | other left | matching right | other right], swapping the middle blocks
+            list[l..mid+r].rotate_left(mid-l);
+            l + r
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check_partition3<T>(list: &[T], pivot: &T, lt: usize, gt: usize) where T: PartialOrd + std::fmt::Debug {
+        assert!(list[..lt].iter().all(|e| e < pivot), "{:?}", list);
+        assert!(list[lt..gt].iter().all(|e| e == pivot), "{:?}", list);
+        assert!(list[gt..].iter().all(|e| e > pivot), "{:?}", list);
+    }
+
+    #[test]
+    fn partition3_empty() {
+        let mut input: Vec<i32> = vec![];
+        assert_eq!(partition3(&mut input, &1), (0, 0));
+    }
+
+    #[test]
+    fn partition3_threshold() {
+        let mut input = vec![5, 1, 9, 5, 3, 7, 5, 0, 10];
+        let (lt, gt) = partition3(&mut input, &5);
+        assert_eq!((lt, gt), (3, 6));
+        check_partition3(&input, &5, lt, gt);
+    }
+
+    #[test]
+    fn partition3_pivot_missing() {
+        let mut input = vec![8, 2, 6, 4];
+        let (lt, gt) = partition3(&mut input, &5);
+        assert_eq!((lt, gt), (2, 2));
+        check_partition3(&input, &5, lt, gt);
+    }
+
+    #[test]
+    fn partition3_by_field() {
+        let mut input = vec![("a", 30), ("b", 10), ("c", 20), ("d", 40), ("e", 20)];
+        let (lt, gt) = partition3_by(&mut input, |e| e.1.cmp(&20));
+        assert_eq!((lt, gt), (1, 3));
+        assert_eq!(input[0], ("b", 10));
+        assert!(input[lt..gt].iter().all(|e| e.1 == 20));
+    }
+
+    #[test]
+    fn partition_by_unstable() {
+        let mut input: Vec<i32> = (0..20).collect();
+        let n = partition_by(&mut input, |e| e % 3 == 0);
+        assert_eq!(n, 7);
+        assert!(input[..n].iter().all(|e| e % 3 == 0));
+        assert!(input[n..].iter().all(|e| e % 3 != 0));
+    }
+
+    #[test]
+    fn partition_stable_keeps_order() {
+        let mut input: Vec<i32> = (0..20).collect();
+        let n = partition_stable_by(&mut input, |e| e % 3 == 0);
+        assert_eq!(n, 7);
+        assert_eq!(input, vec![0, 3, 6, 9, 12, 15, 18, 1, 2, 4, 5, 7, 8, 10, 11, 13, 14, 16, 17, 19]);
+    }
+
+    #[test]
+    fn partition_stable_strings() {
+        let mut input: Vec<String> = vec!["bb", "a", "ccc", "d", "ee"].into_iter().map(String::from).collect();
+        let n = partition_stable_by(&mut input, |s| s.len() == 1);
+        assert_eq!(n, 2);
+        assert_eq!(input, vec!["a", "d", "bb", "ccc", "ee"]);
+    }
+}
@@ -0,0 +1,383 @@
+use std::cmp::Ordering;
+use crate::sort::partial_compare;
+use crate::sort::insertion::insertion_probed;
+use crate::sort::heap::heap_probed;
+use crate::sort::probe::{reverse, swap, NoProbe, Probe};
+
+// Slices of this size or smaller are sorted with insertion sort.
+const MAX_INSERTION: usize = 20;
+// Slices of this size or larger pick the pivot as a median of medians (Tukey's ninther).
+const SHORTEST_NINTHER: usize = 50;
+// Number of swaps choosing the pivot which signals a descending slice.
+const MAX_SWAPS: usize = 4 * 3;
+// Number of out-of-order pairs a partial insertion sort fixes before giving up.
+const MAX_STEPS: usize = 5;
+// Slices shorter than this are not shifted by a partial insertion sort.
+const SHORTEST_SHIFTING: usize = 50;
+
+/// Pattern-defeating quick sort: average and worst run time O(n*logn), O(n) for sorted, reverse sorted
+/// and all-equal slices, space - O(logn).
+pub fn pdq<T>(list: &mut [T]) where T: PartialOrd {
+    pdq_by(list, partial_compare);
+}
+
+/// Pattern-defeating quick sort with a comparator function.
+pub fn pdq_by<T, F>(list: &mut [T], compare: F) where F: FnMut(&T, &T) -> Ordering {
+    pdq_probed(list, compare, &NoProbe);
+}
+
+/// Pattern-defeating quick sort with a key extraction function.
+pub fn pdq_by_key<T, K, F>(list: &mut [T], mut key: F) where F: FnMut(&T) -> K, K: PartialOrd {
+    pdq_by(list, |a, b| partial_compare(&key(a), &key(b)));
+}
+
+pub(crate) fn pdq_probed<T, F, P>(list: &mut [T], mut compare: F, probe: &P)
+    where F: FnMut(&T, &T) -> Ordering, P: Probe<T> + ?Sized {
+    // number of imbalanced partitions allowed before switching to heap sort
+    let limit = (usize::BITS - list.len().leading_zeros()) as usize;
+    pdq_internal(list, &mut compare, None, limit, probe);
+}
+
+fn pdq_internal<'a, T, F, P>(list: &'a mut [T], compare: &mut F, pred: Option<&'a T>, limit: usize, probe: &P)
+    where F: FnMut(&T, &T) -> Ordering, P: Probe<T> + ?Sized {
+    probe.enter();
+    pdq_loop(list, compare, pred, limit, probe);
+    probe.leave();
+}
+
+// Sorts `list`, `pred` is the pivot of the parent partition which is not greater than any element of the list.
+fn pdq_loop<'a, T, F, P>(mut list: &'a mut [T], compare: &mut F, mut pred: Option<&'a T>, mut limit: usize, probe: &P)
+    where F: FnMut(&T, &T) -> Ordering, P: Probe<T> + ?Sized {
+    let mut was_balanced = true;
+    let mut was_partitioned = true;
+
+    loop {
+        let len = list.len();
+        if len <= MAX_INSERTION {
+            insertion_probed(list, &mut *compare, probe);
+            return;
+        }
+
+        if limit == 0 {
+            // too many bad pivots, guaranteeing O(n*logn)
+            heap_probed(list, &mut *compare, probe);
+            return;
+        }
+
+        if !was_balanced {
+            break_patterns(list, probe);
+            limit -= 1;
+        }
+
+        let (pivot, likely_sorted) = choose_pivot(list, compare, probe);
+
+        // the last partition was balanced, did not shuffle elements and the pivot looks like
+        // the slice is already sorted: try to finish it with a few insertions
+        if was_balanced && was_partitioned && likely_sorted && partial_insertion_sort(list, compare, probe) {
+            return;
+        }
+
+        if let Some(p) = pred {
+            if compare(p, &list[pivot]) != Ordering::Less {
+                // pivot equals the predecessor: the elements equal to it are skipped, they are in place
+                let mid = partition_equal(list, pivot, compare, probe);
+                let rest = list;
+                list = &mut rest[mid..];
+                continue;
+            }
+        }
+
+        let (mid, partitioned) = partition(list, pivot, compare, probe);
+        was_balanced = mid.min(len - mid) >= len / 8;
+        was_partitioned = partitioned;
+
+        let rest = list;
+        let (left, right) = rest.split_at_mut(mid);
+        let (pivot, right) = right.split_at_mut(1);
+        let pivot = &pivot[0];
+
+        // smaller side first as in `intro`, the pivot is the predecessor of the right side
+        if left.len() < right.len() {
+            pdq_internal(left, compare, pred, limit, probe);
+            list = right;
+            pred = Some(pivot);
+        } else {
+            pdq_internal(right, compare, Some(pivot), limit, probe);
+            list = left;
+        }
+    }
+}
+
+// Partitions the list around list[pivot] into elements less than the pivot followed by elements
+// greater or equal. Returns the final pivot index and whether the list was already partitioned.
+fn partition<T, F, P>(list: &mut [T], pivot: usize, compare: &mut F, probe: &P) -> (usize, bool)
+    where F: FnMut(&T, &T) -> Ordering, P: Probe<T> + ?Sized {
+    swap(list, 0, pivot, probe);
+    let (head, rest) = list.split_at_mut(1);
+    let p = &head[0];
+
+    let mut l = 0;
+    let mut r = rest.len();
+    while l < r && compare(&rest[l], p) == Ordering::Less {
+        l += 1;
+    }
+    while l < r && compare(&rest[r-1], p) != Ordering::Less {
+        r -= 1;
+    }
+    let was_partitioned = l >= r;
+
+    loop {
+        while l < r && compare(&rest[l], p) == Ordering::Less {
+            l += 1;
+        }
+        while l < r && compare(&rest[r-1], p) != Ordering::Less {
+            r -= 1;
+        }
+        if l >= r {
+            break;
+        }
+        r -= 1;
+        swap(rest, l, r, probe);
+        l += 1;
+    }
+
+    swap(list, 0, l, probe);
+    (l, was_partitioned)
+}
+
+// Partitions the list into elements equal to list[pivot] followed by greater ones, assuming
+// no element is less than the pivot. Returns the number of elements equal to the pivot.
+fn partition_equal<T, F, P>(list: &mut [T], pivot: usize, compare: &mut F, probe: &P) -> usize
+    where F: FnMut(&T, &T) -> Ordering, P: Probe<T> + ?Sized {
+    swap(list, 0, pivot, probe);
+    let (head, rest) = list.split_at_mut(1);
+    let p = &head[0];
+
+    let mut l = 0;
+    let mut r = rest.len();
+    loop {
+        while l < r && compare(p, &rest[l]) != Ordering::Less {
+            l += 1;
+        }
+        while l < r && compare(p, &rest[r-1]) == Ordering::Less {
+            r -= 1;
+        }
+        if l >= r {
+            break;
+        }
+        r -= 1;
+        swap(rest, l, r, probe);
+        l += 1;
+    }
+
+    // including the pivot itself
+    l + 1
+}
+
+// Chooses a pivot by median of three or ninther. Returns its index and whether the list looks sorted.
+// A list which looks descending is reversed.
+pub(crate) fn choose_pivot<T, F, P>(list: &mut [T], compare: &mut F, probe: &P) -> (usize, bool)
+    where F: FnMut(&T, &T) -> Ordering, P: Probe<T> + ?Sized {
+    let len = list.len();
+    let mut a = len / 4;
+    let mut b = len / 4 * 2;
+    let mut c = len / 4 * 3;
+    let mut swaps = 0;
+
+    if len >= 8 {
+        if len >= SHORTEST_NINTHER {
+            a = median_of_adjacent(list, a, &mut swaps, compare);
+            b = median_of_adjacent(list, b, &mut swaps, compare);
+            c = median_of_adjacent(list, c, &mut swaps, compare);
+        }
+        b = median_of_three(list, a, b, c, &mut swaps, compare);
+    }
+
+    if swaps < MAX_SWAPS {
+        (b, swaps == 0)
+    } else {
+        // every comparison was out of order, the list is likely descending
+        reverse(list, probe);
+        (len - 1 - b, true)
+    }
+}
+
+fn median_of_adjacent<T, F>(list: &[T], i: usize, swaps: &mut usize, compare: &mut F) -> usize
+    where F: FnMut(&T, &T) -> Ordering {
+    median_of_three(list, i-1, i, i+1, swaps, compare)
+}
+
+// Returns the index of the median of list[a], list[b] and list[c], counting out of order pairs.
+fn median_of_three<T, F>(list: &[T], a: usize, b: usize, c: usize, swaps: &mut usize, compare: &mut F) -> usize
+    where F: FnMut(&T, &T) -> Ordering {
+    let (mut a, mut b, mut c) = (a, b, c);
+    let mut sort2 = |x: &mut usize, y: &mut usize| {
+        if compare(&list[*y], &list[*x]) == Ordering::Less {
+            std::mem::swap(x, y);
+            *swaps += 1;
+        }
+    };
+    sort2(&mut a, &mut b);
+    sort2(&mut b, &mut c);
+    sort2(&mut a, &mut b);
+    b
+}
+
+// Fixes a few out of order pairs with insertions. Returns true if the list ends up sorted.
+fn partial_insertion_sort<T, F, P>(list: &mut [T], compare: &mut F, probe: &P) -> bool
+    where F: FnMut(&T, &T) -> Ordering, P: Probe<T> + ?Sized {
+    let len = list.len();
+    let mut i = 1;
+    for _ in 0..MAX_STEPS {
+        while i < len && compare(&list[i], &list[i-1]) != Ordering::Less {
+            i += 1;
+        }
+        if i == len {
+            return true;
+        }
+        if len < SHORTEST_SHIFTING {
+            return false;
+        }
+
+        swap(list, i-1, i, probe);
+        shift_tail(&mut list[..i], compare, probe);
+        shift_head(&mut list[i..], compare, probe);
+    }
+    false
+}
+
+// Moves the last element left until it is in order.
+fn shift_tail<T, F, P>(list: &mut [T], compare: &mut F, probe: &P) where F: FnMut(&T, &T) -> Ordering, P: Probe<T> + ?Sized {
+    let mut j = list.len();
+    while j > 1 && compare(&list[j-1], &list[j-2]) == Ordering::Less {
+        swap(list, j-1, j-2, probe);
+        j -= 1;
+    }
+}
+
+// Moves the first element right until it is in order.
+fn shift_head<T, F, P>(list: &mut [T], compare: &mut F, probe: &P) where F: FnMut(&T, &T) -> Ordering, P: Probe<T> + ?Sized {
+    let mut j = 0;
+    while j+1 < list.len() && compare(&list[j+1], &list[j]) == Ordering::Less {
+        swap(list, j, j+1, probe);
+        j += 1;
+    }
+}
+
+// Swaps a few elements around the middle with pseudo-random positions to break patterns
+// which cause imbalanced partitions.
+fn break_patterns<T, P>(list: &mut [T], probe: &P) where P: Probe<T> + ?Sized {
+    let len = list.len();
+    if len < 8 {
+        return;
+    }
+
+    // xorshift, deterministic for reproducibility
+    let mut seed = len as u64;
+    let mut gen = || {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        seed as usize
+    };
+
+    let mask = len.next_power_of_two() - 1;
+    let pos = len / 4 * 2;
+    for i in 0..3 {
+        let mut other = gen() & mask;
+        if other >= len {
+            other -= len;
+        }
+        swap(list, pos - 1 + i, other, probe);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check_order<T>(list: &[T]) where T: PartialOrd + std::fmt::Debug {
+        let mut prev: &T = &list[0];
+        for a in list.iter() {
+            assert!(a >= prev, "non-decreasing order violation: {:?}", list);
+            prev = a;
+        }
+    }
+
+    #[test]
+    fn pdq_two_elements() {
+        let mut input = vec![2, 1];
+        pdq(&mut input);
+        check_order(&input);
+    }
+
+    #[test]
+    fn pdq_basic_sort() {
+        let mut input = vec![1, 3, 2, 5, 4];
+        pdq(&mut input);
+        check_order(&input);
+    }
+
+    #[test]
+    fn pdq_asc_sort() {
+        let mut input: Vec<i32> = (0..1000).collect();
+        pdq(&mut input);
+        check_order(&input);
+    }
+
+    #[test]
+    fn pdq_desc_sort() {
+        let mut input: Vec<i32> = (0..1000).rev().collect();
+        pdq(&mut input);
+        check_order(&input);
+    }
+
+    #[test]
+    fn pdq_many_duplicates() {
+        let mut input: Vec<i32> = (0..5000).map(|i| (i * 7919) % 3).collect();
+        pdq(&mut input);
+        check_order(&input);
+    }
+
+    #[test]
+    fn pdq_patterns() {
+        let sawtooth: Vec<i32> = (0..2000).map(|i| i % 37).collect();
+        let organ_pipe: Vec<i32> = (0..1000).chain((0..1000).rev()).collect();
+        let pseudo_random: Vec<i32> = (0..2000).map(|i| (i * 7919) % 2003).collect();
+        for input in [sawtooth, organ_pipe, pseudo_random].iter_mut() {
+            let mut expected = input.clone();
+            expected.sort();
+            pdq(input);
+            assert_eq!(input, &expected);
+        }
+    }
+
+    #[test]
+    fn pdq_by_desc() {
+        let mut input: Vec<i32> = (0..100).collect();
+        pdq_by(&mut input, |a, b| b.cmp(a));
+        assert_eq!(input, (0..100).rev().collect::<Vec<i32>>());
+    }
+
+    #[test]
+    fn pdq_by_key_field() {
+        let mut input = vec![(1, 'c'), (2, 'a'), (3, 'b')];
+        pdq_by_key(&mut input, |e| e.1);
+        assert_eq!(input, vec![(2, 'a'), (3, 'b'), (1, 'c')]);
+    }
+
+    #[test]
+    fn pdq_linear_on_presorted() {
+        let n = 10000;
+        let inputs: Vec<Vec<i32>> = vec![(0..n).collect(), (0..n).rev().collect(), vec![1; n as usize]];
+        for mut input in inputs {
+            let mut comparisons = 0;
+            pdq_by(&mut input, |a, b| {
+                comparisons += 1;
+                a.cmp(b)
+            });
+            check_order(&input);
+            assert!(comparisons < 2 * n, "{} comparisons", comparisons);
+        }
+    }
+}
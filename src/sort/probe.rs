@@ -0,0 +1,57 @@
+use std::cmp::Ordering;
+
+/// Observer of the element operations made by a sort, used to instrument the algorithms.
+/// Elements are passed by reference, so a probe can tell their positions from the addresses.
+/// All methods do nothing by default.
+pub trait Probe<T> {
+    /// Called before two elements are compared, they may live outside the slice (buffer, pivot).
+    fn compare(&self, _a: &T, _b: &T) {}
+
+    /// Called before two elements of the slice are exchanged.
+    fn swap(&self, _a: &T, _b: &T) {}
+
+    /// Called after an element is moved into the slice position from outside of it,
+    /// from another position by a merge or by a rotation.
+    fn write(&self, _dst: &T) {}
+
+    /// Called when a recursive call starts working on a part of the slice.
+    fn enter(&self) {}
+
+    /// Called when the matching recursive call is done.
+    fn leave(&self) {}
+}
+
+/// Probe ignoring every operation, the uninstrumented sorts compile to the same code as without it.
+pub struct NoProbe;
+
+impl<T> Probe<T> for NoProbe {}
+
+// Comparator reporting each comparison to the probe.
+pub(crate) fn probed<'a, T, F, P>(mut compare: F, probe: &'a P) -> impl FnMut(&T, &T) -> Ordering + 'a
+    where F: FnMut(&T, &T) -> Ordering + 'a, P: Probe<T> + ?Sized {
+    move |a, b| {
+        probe.compare(a, b);
+        compare(a, b)
+    }
+}
+
+// Swaps two elements of the list, reporting it to the probe.
+pub(crate) fn swap<T, P>(list: &mut [T], i: usize, j: usize, probe: &P) where P: Probe<T> + ?Sized {
+    probe.swap(&list[i], &list[j]);
+    list.swap(i, j);
+}
+
+// Reverses the list with swaps reported to the probe.
+pub(crate) fn reverse<T, P>(list: &mut [T], probe: &P) where P: Probe<T> + ?Sized {
+    let n = list.len();
+    for i in 0..n/2 {
+        swap(list, i, n-1-i, probe);
+    }
+}
+
+// Reports writes to every element of the list, after it was filled from outside or rotated.
+pub(crate) fn written<T, P>(list: &[T], probe: &P) where P: Probe<T> + ?Sized {
+    for e in list.iter() {
+        probe.write(e);
+    }
+}
@@ -1,37 +1,85 @@
-use rand::Rng;
+use std::cmp::Ordering;
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use crate::sort::partial_compare;
+use crate::sort::probe::{swap, NoProbe, Probe};
 
 /// Quick sort: average run time O(n*logn), space - O(1)
-pub fn quick<T>(list: &mut [T]) where T: std::cmp::PartialOrd {
-    quick_internal(list, 0, list.len()-1);
+pub fn quick<T>(list: &mut [T]) where T: PartialOrd {
+    quick_by(list, partial_compare);
 }
 
-fn quick_internal<T>(list: &mut [T], lo: usize, hi: usize) where T: std::cmp::PartialOrd {
+/// Quick sort with a comparator function.
+pub fn quick_by<T, F>(list: &mut [T], compare: F) where F: FnMut(&T, &T) -> Ordering {
+    quick_by_with_rng(list, &mut rand::thread_rng(), compare);
+}
+
+/// Quick sort with a key extraction function.
+pub fn quick_by_key<T, K, F>(list: &mut [T], mut key: F) where F: FnMut(&T) -> K, K: PartialOrd {
+    quick_by(list, |a, b| partial_compare(&key(a), &key(b)));
+}
+
+/// Quick sort picking pivots with the given random number generator.
+pub fn quick_with_rng<T, R>(list: &mut [T], rng: &mut R) where T: PartialOrd, R: Rng + ?Sized {
+    quick_by_with_rng(list, rng, partial_compare);
+}
+
+/// Quick sort with a comparator function picking pivots with the given random number generator.
+pub fn quick_by_with_rng<T, R, F>(list: &mut [T], rng: &mut R, compare: F)
+    where R: Rng + ?Sized, F: FnMut(&T, &T) -> Ordering {
+    quick_probed(list, rng, compare, &NoProbe);
+}
+
+/// Quick sort with pivots picked by a generator seeded with `seed`, the same seed replays the same
+/// sequence of partitions.
+pub fn quick_seeded<T>(list: &mut [T], seed: u64) where T: PartialOrd {
+    quick_with_rng(list, &mut StdRng::seed_from_u64(seed));
+}
+
+pub(crate) fn quick_probed<T, R, F, P>(list: &mut [T], rng: &mut R, mut compare: F, probe: &P)
+    where R: Rng + ?Sized, F: FnMut(&T, &T) -> Ordering, P: Probe<T> + ?Sized {
+    if list.is_empty() {
+        return;
+    }
+    quick_internal(list, 0, list.len()-1, rng, &mut compare, probe);
+}
+
+fn quick_internal<T, R, F, P>(list: &mut [T], lo: usize, hi: usize, rng: &mut R, compare: &mut F, probe: &P)
+    where R: Rng + ?Sized, F: FnMut(&T, &T) -> Ordering, P: Probe<T> + ?Sized {
     if lo >= hi {
         return;
     }
 
-    let p = quick_partition(list, lo, hi);
+    probe.enter();
+    let p = quick_partition(list, lo, hi, rng, compare, probe);
     if p > 0 {
-        quick_internal(list, lo, p-1);    
+        quick_internal(list, lo, p-1, rng, compare, probe);
     }
-    quick_internal(list, p+1, hi);
+    quick_internal(list, p+1, hi, rng, compare, probe);
+    probe.leave();
 }
 
-fn quick_partition<T>(list: &mut [T], lo: usize, hi: usize) -> usize 
-    where T: std::cmp::PartialOrd {
-    let mut rng = rand::thread_rng();
+pub(crate) fn quick_partition<T, R, F, P>(list: &mut [T], lo: usize, hi: usize, rng: &mut R, compare: &mut F, probe: &P) -> usize
+    where R: Rng + ?Sized, F: FnMut(&T, &T) -> Ordering, P: Probe<T> + ?Sized {
     let pivot_i = lo + rng.gen_range(0, hi-lo+1);
-    list.swap(pivot_i, hi);
-    
+    partition_around(list, lo, hi, pivot_i, compare, probe)
+}
+
+// Partitions list[lo..hi+1] into elements less than list[pivot_i], the pivot itself and elements
+// greater or equal. Returns the final index of the pivot.
+pub(crate) fn partition_around<T, F, P>(list: &mut [T], lo: usize, hi: usize, pivot_i: usize, compare: &mut F, probe: &P) -> usize
+    where F: FnMut(&T, &T) -> Ordering, P: Probe<T> + ?Sized {
+    swap(list, pivot_i, hi, probe);
+
     let mut i = lo;
     for j in lo..hi {
-        if list[j] < list[hi] {
-            list.swap(i, j);
+        if compare(&list[j], &list[hi]) == Ordering::Less {
+            swap(list, i, j, probe);
             i += 1;
         }
     }
-    list.swap(i, hi);
-    return i;
+    swap(list, i, hi, probe);
+    i
 }
 
 #[cfg(test)]
@@ -73,5 +121,58 @@ mod test {
         let mut input = vec![5, 4, 3, 2, 1];
         quick(&mut input);
         check_order(&input);
-    }    
+    }
+
+    #[test]
+    fn quick_by_desc() {
+        let mut input = vec![1, 3, 2, 5, 4];
+        quick_by(&mut input, |a, b| b.cmp(a));
+        assert_eq!(input, vec![5, 4, 3, 2, 1]);
+    }
+
+    #[test]
+    fn quick_by_key_field() {
+        let mut input = vec![(1, 'c'), (2, 'a'), (3, 'b')];
+        quick_by_key(&mut input, |e| e.1);
+        assert_eq!(input, vec![(2, 'a'), (3, 'b'), (1, 'c')]);
+    }
+
+    #[test]
+    fn quick_with_rng_sort() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut input = vec![5, 1, 4, 2, 3, 3, 0];
+        quick_with_rng(&mut input, &mut rng);
+        check_order(&input);
+    }
+
+    #[test]
+    fn quick_seeded_replays() {
+        fn comparisons(seed: u64) -> Vec<(i32, i32)> {
+            let mut input: Vec<i32> = (0..100).map(|i| (i * 37) % 101).collect();
+            let mut log = Vec::new();
+            quick_by_with_rng(&mut input, &mut StdRng::seed_from_u64(seed), |a, b| {
+                log.push((*a, *b));
+                a.cmp(b)
+            });
+            log
+        }
+
+        assert_eq!(comparisons(42), comparisons(42));
+        assert_ne!(comparisons(42), comparisons(43));
+
+        let mut input = vec![5, 1, 4, 2, 3];
+        quick_seeded(&mut input, 42);
+        check_order(&input);
+    }
+
+    #[test]
+    fn quick_empty_and_single() {
+        let mut input: Vec<i32> = vec![];
+        quick(&mut input);
+        assert!(input.is_empty());
+
+        let mut input = vec![1];
+        quick(&mut input);
+        assert_eq!(input, vec![1]);
+    }
 }
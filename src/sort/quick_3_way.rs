@@ -1,36 +1,52 @@
+use std::cmp::Ordering;
+use crate::sort::partial_compare;
+use crate::sort::partition::partition3_probed;
+use crate::sort::probe::{swap, NoProbe, Probe};
+
 /// Quick sort (3-way edition for inputs with many repeated elements): O(n*logn)
-pub fn quick_3_way<T>(list: &mut [T]) where T: PartialOrd + Copy {
-    quick_internal(list, 0, list.len()-1);
+pub fn quick_3_way<T>(list: &mut [T]) where T: PartialOrd {
+    quick_3_way_by(list, partial_compare);
+}
+
+/// Quick sort (3-way edition) with a comparator function.
+pub fn quick_3_way_by<T, F>(list: &mut [T], compare: F) where F: FnMut(&T, &T) -> Ordering {
+    quick_3_way_probed(list, compare, &NoProbe);
+}
+
+/// Quick sort (3-way edition) with a key extraction function.
+pub fn quick_3_way_by_key<T, K, F>(list: &mut [T], mut key: F) where F: FnMut(&T) -> K, K: PartialOrd {
+    quick_3_way_by(list, |a, b| partial_compare(&key(a), &key(b)));
+}
+
+pub(crate) fn quick_3_way_probed<T, F, P>(list: &mut [T], mut compare: F, probe: &P)
+    where F: FnMut(&T, &T) -> Ordering, P: Probe<T> + ?Sized {
+    quick_internal(list, &mut compare, probe);
 }
 
-fn quick_internal<T>(list: &mut [T], lo: usize, hi: usize) where T: PartialOrd + Copy {
-    if lo >= hi || hi >= list.len() {
+fn quick_internal<T, F, P>(list: &mut [T], compare: &mut F, probe: &P) where F: FnMut(&T, &T) -> Ordering, P: Probe<T> + ?Sized {
+    if list.len() < 2 {
         return;
     }
 
-    let mut l = lo;
-    let mut g = hi;
-    let mut i = lo;
-    let p = list[i];
-    while i <= g {
-        if list[i] < p {
-            list.swap(l, i);
-            l += 1;
-            i += 1;
-        } else if list[i] > p {
-            list.swap(i, g);
-            g -= 1;
-        } else {
-            i += 1;
-        }
-        
-    }
+    probe.enter();
+    let (lt, gt) = partition_around_first(list, compare, probe);
+    let (less, rest) = list.split_at_mut(lt);
+    quick_internal(less, compare, probe);
+    quick_internal(&mut rest[gt-lt..], compare, probe);
+    probe.leave();
+}
 
-    // excluding list[l..g+1] where all elements equal to p
-    if l > lo {
-        quick_internal(list, lo, l-1);    
-    }
-    quick_internal(list, g+1, hi);
+// Partitions the non-empty list around its first element. Returns `(lt, gt)`: list[..lt] are less
+// than the pivot, list[lt..gt] equal to it and list[gt..] greater.
+pub(crate) fn partition_around_first<T, F, P>(list: &mut [T], compare: &mut F, probe: &P) -> (usize, usize)
+    where F: FnMut(&T, &T) -> Ordering, P: Probe<T> + ?Sized {
+    // list[0] is the pivot, the rest is partitioned around it
+    let (head, rest) = list.split_at_mut(1);
+    let (lt, gt) = partition3_probed(rest, |e| compare(e, &head[0]), probe);
+
+    // moving the pivot between the less and equal parts
+    swap(list, 0, lt, probe);
+    (lt, gt+1)
 }
 
 #[cfg(test)]
@@ -86,4 +102,29 @@ mod test {
         quick_3_way(&mut input);
         check_order(&input);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn quick3_by_desc() {
+        let mut input = vec![1, 3, 2, 5, 4, 3, 1];
+        quick_3_way_by(&mut input, |a, b| b.cmp(a));
+        assert_eq!(input, vec![5, 4, 3, 3, 2, 1, 1]);
+    }
+
+    #[test]
+    fn quick3_by_key_field() {
+        let mut input = vec![(1, 'c'), (2, 'a'), (3, 'b')];
+        quick_3_way_by_key(&mut input, |e| e.1);
+        assert_eq!(input, vec![(2, 'a'), (3, 'b'), (1, 'c')]);
+    }
+
+    #[test]
+    fn quick3_empty_and_single() {
+        let mut input: Vec<i32> = vec![];
+        quick_3_way(&mut input);
+        assert!(input.is_empty());
+
+        let mut input = vec![1];
+        quick_3_way(&mut input);
+        assert_eq!(input, vec![1]);
+    }
+}
@@ -0,0 +1,234 @@
+use crate::sort::apply_order;
+
+// Default number of bits sorted per pass.
+const DEFAULT_WIDTH: u32 = 8;
+
+/// Integer key usable by radix sort: maps the value to an unsigned integer with the same order.
+pub trait RadixKey: Copy {
+    /// Number of significant bits of the key.
+    const BITS: u32;
+
+    fn radix(self) -> u64;
+}
+
+macro_rules! radix_key_unsigned {
+    ($($t:ty),*) => {$(
+        impl RadixKey for $t {
+            const BITS: u32 = <$t>::BITS;
+
+            fn radix(self) -> u64 {
+                self as u64
+            }
+        }
+    )*}
+}
+
+macro_rules! radix_key_signed {
+    ($($t:ty => $u:ty),*) => {$(
+        impl RadixKey for $t {
+            const BITS: u32 = <$t>::BITS;
+
+            fn radix(self) -> u64 {
+                // flipping the sign bit puts negative values before positive ones
+                ((self as $u) ^ (1 << (<$t>::BITS - 1))) as u64
+            }
+        }
+    )*}
+}
+
+radix_key_unsigned!(u8, u16, u32, u64, usize);
+radix_key_signed!(i8 => u8, i16 => u16, i32 => u32, i64 => u64, isize => usize);
+
+/// LSD radix sort of integers: run time O(n*w/8) for w-bit integers, space - O(n).
+pub fn radix_sort<T>(list: &mut [T]) where T: RadixKey {
+    radix_sort_with(list, DEFAULT_WIDTH);
+}
+
+/// LSD radix sort sorting `width` bits per pass, `width` in [1, 16]. Wider digits mean fewer passes
+/// over the list but larger count tables.
+pub fn radix_sort_with<T>(list: &mut [T], width: u32) where T: RadixKey {
+    assert!((1..=16).contains(&width), "digit width {} out of range [1, 16]", width);
+    lsd(list, T::BITS, width, |x| x.radix());
+}
+
+/// Stable LSD radix sort of records by an integer key. Keys are extracted once per element and
+/// sorted along with element indices, then the records are moved into place with swaps.
+pub fn radix_sort_by_key<T, K, F>(list: &mut [T], key: F) where K: RadixKey, F: FnMut(&T) -> K {
+    radix_sort_by_key_with(list, DEFAULT_WIDTH, key);
+}
+
+/// Stable LSD radix sort of records by an integer key sorting `width` bits per pass, `width` in [1, 16].
+pub fn radix_sort_by_key_with<T, K, F>(list: &mut [T], width: u32, mut key: F) where K: RadixKey, F: FnMut(&T) -> K {
+    assert!((1..=16).contains(&width), "digit width {} out of range [1, 16]", width);
+    let mut pairs: Vec<(u64, usize)> = list.iter()
+        .enumerate()
+        .map(|(i, e)| (key(e).radix(), i))
+        .collect();
+    lsd(&mut pairs, K::BITS, width, |p| p.0);
+
+    let mut order: Vec<usize> = pairs.into_iter().map(|p| p.1).collect();
+    apply_order(list, &mut order);
+}
+
+// Sorts the list by the lowest `bits` bits of the key, `width` bits per pass.
+fn lsd<T, F>(list: &mut [T], bits: u32, width: u32, key: F) where T: Copy, F: Fn(&T) -> u64 {
+    let n = list.len();
+    if n < 2 {
+        return;
+    }
+
+    let radix = 1usize << width;
+    let mask = (radix - 1) as u64;
+    let mut aux = list.to_vec();
+    let mut count = vec![0usize; radix + 1];
+    // elements are distributed back and forth between the list and the buffer
+    let mut in_list = true;
+
+    for shift in (0..bits).step_by(width as usize) {
+        let (src, dst) = if in_list {
+            (&*list, &mut aux[..])
+        } else {
+            (&aux[..], &mut *list)
+        };
+        let digit = |e: &T| ((key(e) >> shift) & mask) as usize;
+
+        for c in count.iter_mut() {
+            *c = 0;
+        }
+        for e in src.iter() {
+            count[digit(e) + 1] += 1;
+        }
+        if count.contains(&n) {
+            // all elements share the digit, the pass would not change the order
+            continue;
+        }
+
+        for r in 0..radix {
+            count[r + 1] += count[r];
+        }
+        for e in src.iter() {
+            let d = digit(e);
+            dst[count[d]] = *e;
+            count[d] += 1;
+        }
+        in_list = !in_list;
+    }
+
+    if !in_list {
+        list.copy_from_slice(&aux);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate test;
+
+    use super::*;
+    use rand::Rng;
+    use test::Bencher;
+
+    fn check_order<T>(list: &[T]) where T: PartialOrd + std::fmt::Debug {
+        let mut prev: &T = &list[0];
+        for a in list.iter() {
+            assert!(a >= prev, "non-decreasing order violation: {:?}", list);
+            prev = a;
+        }
+    }
+
+    #[test]
+    fn radix_empty_and_single() {
+        let mut input: Vec<u32> = vec![];
+        radix_sort(&mut input);
+        assert!(input.is_empty());
+
+        let mut input = vec![7u32];
+        radix_sort(&mut input);
+        assert_eq!(input, vec![7]);
+    }
+
+    #[test]
+    fn radix_unsigned() {
+        let mut input: Vec<u64> = (0..1000u64).map(|i| i.wrapping_mul(0x9E37_79B9_7F4A_7C15)).collect();
+        radix_sort(&mut input);
+        check_order(&input);
+    }
+
+    #[test]
+    fn radix_signed() {
+        let mut input = vec![3i64, -1, i64::MIN, 0, i64::MAX, -42, 42];
+        radix_sort(&mut input);
+        assert_eq!(input, vec![i64::MIN, -42, -1, 0, 3, 42, i64::MAX]);
+
+        let mut input: Vec<i8> = (-128..128).rev().map(|i| i as i8).collect();
+        radix_sort(&mut input);
+        check_order(&input);
+    }
+
+    #[test]
+    fn radix_widths() {
+        let expected: Vec<i32> = (-500..500).collect();
+        for width in 1..=16 {
+            let mut input: Vec<i32> = expected.iter().rev().cloned().collect();
+            radix_sort_with(&mut input, width);
+            assert_eq!(input, expected, "width {}", width);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn radix_width_out_of_range() {
+        let mut input = vec![2u8, 1];
+        radix_sort_with(&mut input, 17);
+    }
+
+    #[test]
+    fn radix_by_key_stable() {
+        let mut input: Vec<(i32, String)> = vec![(2, "a"), (-1, "b"), (2, "c"), (-1, "d"), (0, "e")]
+            .into_iter().map(|(k, v)| (k, v.to_string())).collect();
+        radix_sort_by_key(&mut input, |e| e.0);
+        let values: Vec<&str> = input.iter().map(|e| e.1.as_str()).collect();
+        assert_eq!(values, vec!["b", "d", "e", "a", "c"]);
+    }
+
+    #[test]
+    fn radix_by_key_widths() {
+        let original: Vec<(i64, usize)> = (0..2000).map(|i| (((i * 7919) % 401) as i64 - 200, i)).collect();
+        let mut expected = original.clone();
+        expected.sort_by_key(|e| e.0);
+        for &width in [4, 8, 16].iter() {
+            let mut input = original.clone();
+            radix_sort_by_key_with(&mut input, width, |e| e.0);
+            assert_eq!(input, expected, "width {}", width);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn radix_by_key_width_out_of_range() {
+        let mut input = vec![(2u8, 'a'), (1, 'b')];
+        radix_sort_by_key_with(&mut input, 0, |e| e.0);
+    }
+
+    fn random_u64(n: usize) -> Vec<u64> {
+        let mut rng = rand::thread_rng();
+        (0..n).map(|_| rng.gen::<u64>()).collect()
+    }
+
+    #[bench]
+    fn bench_radix_100000_u64(b: &mut Bencher) {
+        let input = random_u64(100000);
+        b.iter(|| {
+            let mut list = input.clone();
+            radix_sort(&mut list);
+        });
+    }
+
+    #[bench]
+    fn bench_quick_100000_u64(b: &mut Bencher) {
+        let input = random_u64(100000);
+        b.iter(|| {
+            let mut list = input.clone();
+            crate::sort::quick(&mut list);
+        });
+    }
+}
@@ -0,0 +1,212 @@
+use std::cmp::Ordering;
+use crate::sort::partial_compare;
+use crate::sort::insertion::insertion_by;
+use crate::sort::quick::{quick_partition, partition_around};
+use crate::sort::probe::NoProbe;
+
+/// Quickselect: reorders the list so the element at index `k` is the one that would be there if
+/// the list was sorted, smaller or equal elements before it and greater or equal after it.
+/// Returns the k-th smallest element. Run time O(n) average case, O(n^2) worst case, space - O(1).
+pub fn select_nth_unstable<T>(list: &mut [T], k: usize) -> &T where T: PartialOrd {
+    select_nth_unstable_by(list, k, partial_compare)
+}
+
+/// Quickselect with a comparator function.
+pub fn select_nth_unstable_by<T, F>(list: &mut [T], k: usize, mut compare: F) -> &T
+    where F: FnMut(&T, &T) -> Ordering {
+    assert!(k < list.len(), "index {} out of range for length {}", k, list.len());
+
+    let mut rng = rand::thread_rng();
+    select_internal(list, k, &mut compare, |list, compare| {
+        quick_partition(list, 0, list.len()-1, &mut rng, compare, &NoProbe)
+    });
+    &list[k]
+}
+
+/// Quickselect with a key extraction function.
+pub fn select_nth_unstable_by_key<T, K, F>(list: &mut [T], k: usize, mut key: F) -> &T
+    where F: FnMut(&T) -> K, K: PartialOrd {
+    select_nth_unstable_by(list, k, |a, b| partial_compare(&key(a), &key(b)))
+}
+
+/// Selection with median-of-medians pivots: same result as `select_nth_unstable`,
+/// run time O(n) worst case, space - O(logn).
+pub fn select_nth_linear<T>(list: &mut [T], k: usize) -> &T where T: PartialOrd {
+    select_nth_linear_by(list, k, partial_compare)
+}
+
+/// Selection with median-of-medians pivots and a comparator function.
+pub fn select_nth_linear_by<T, F>(list: &mut [T], k: usize, mut compare: F) -> &T
+    where F: FnMut(&T, &T) -> Ordering {
+    assert!(k < list.len(), "index {} out of range for length {}", k, list.len());
+
+    select_linear(list, k, &mut compare);
+    &list[k]
+}
+
+/// Selection with median-of-medians pivots and a key extraction function.
+pub fn select_nth_linear_by_key<T, K, F>(list: &mut [T], k: usize, mut key: F) -> &T
+    where F: FnMut(&T) -> K, K: PartialOrd {
+    select_nth_linear_by(list, k, |a, b| partial_compare(&key(a), &key(b)))
+}
+
+/// Lower median of the list, reorders the list. Returns `None` for an empty list.
+pub fn median<T>(list: &mut [T]) -> Option<&T> where T: PartialOrd {
+    if list.is_empty() {
+        return None;
+    }
+    let k = (list.len()-1)/2;
+    Some(select_nth_unstable(list, k))
+}
+
+/// Nearest-rank percentile of the list for `p` in [0, 100], reorders the list.
+/// Returns `None` for an empty list.
+pub fn percentile<T>(list: &mut [T], p: f64) -> Option<&T> where T: PartialOrd {
+    assert!((0.0..=100.0).contains(&p), "percentile {} out of range [0, 100]", p);
+    if list.is_empty() {
+        return None;
+    }
+    let rank = (p / 100.0 * list.len() as f64).ceil() as usize;
+    let k = rank.max(1) - 1;
+    Some(select_nth_unstable(list, k))
+}
+
+// Narrows list down around index k, `partition` places a pivot and returns its index.
+fn select_internal<T, F, P>(list: &mut [T], k: usize, compare: &mut F, mut partition: P)
+    where F: FnMut(&T, &T) -> Ordering, P: FnMut(&mut [T], &mut F) -> usize {
+    let mut lo = 0;
+    let mut hi = list.len();
+    while hi - lo > 1 {
+        let p = lo + partition(&mut list[lo..hi], compare);
+        if k == p {
+            return;
+        }
+        if k < p {
+            hi = p;
+            continue;
+        }
+
+        // elements equal to the pivot are moved next to it, so runs of equal elements
+        // do not degrade to one element per round
+        let (head, rest) = list.split_at_mut(p+1);
+        let equal = group_equal(&mut rest[..hi-p-1], &head[p], compare);
+        lo = p + 1 + equal;
+        if k < lo {
+            return;
+        }
+    }
+}
+
+// Moves elements not greater than the pivot to the front, returns their number. All elements of the list
+// are expected to be greater or equal to the pivot.
+fn group_equal<T, F>(list: &mut [T], pivot: &T, compare: &mut F) -> usize where F: FnMut(&T, &T) -> Ordering {
+    let mut i = 0;
+    for j in 0..list.len() {
+        if compare(pivot, &list[j]) != Ordering::Less {
+            list.swap(i, j);
+            i += 1;
+        }
+    }
+    i
+}
+
+fn select_linear<T, F>(list: &mut [T], k: usize, compare: &mut F) where F: FnMut(&T, &T) -> Ordering {
+    select_internal(list, k, compare, |list, compare| {
+        let pivot = median_of_medians(list, compare);
+        partition_around(list, 0, list.len()-1, pivot, compare, &NoProbe)
+    });
+}
+
+// Returns the index of a pivot that has at least 3/10 of the elements on either side.
+fn median_of_medians<T, F>(list: &mut [T], compare: &mut F) -> usize where F: FnMut(&T, &T) -> Ordering {
+    let n = list.len();
+    if n <= 5 {
+        insertion_by(list, &mut *compare);
+        return n/2;
+    }
+
+    // medians of groups of 5 are gathered at the front of the list
+    let groups = n/5;
+    for g in 0..groups {
+        insertion_by(&mut list[g*5..g*5+5], &mut *compare);
+        list.swap(g, g*5+2);
+    }
+
+    let mid = groups/2;
+    select_linear(&mut list[..groups], mid, compare);
+    mid
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check_selected<T>(list: &[T], k: usize) where T: PartialOrd + std::fmt::Debug {
+        for a in list[..k].iter() {
+            assert!(a <= &list[k], "smaller elements expected before index {}: {:?}", k, list);
+        }
+        for a in list[k+1..].iter() {
+            assert!(a >= &list[k], "greater elements expected after index {}: {:?}", k, list);
+        }
+    }
+
+    #[test]
+    fn select_single() {
+        let mut input = vec![1];
+        assert_eq!(select_nth_unstable(&mut input, 0), &1);
+    }
+
+    #[test]
+    fn select_every_index() {
+        let input: Vec<i32> = (0..50).map(|i| (i * 37) % 53).collect();
+        let mut sorted = input.clone();
+        sorted.sort();
+        for (k, expected) in sorted.iter().enumerate() {
+            let mut list = input.clone();
+            assert_eq!(select_nth_unstable(&mut list, k), expected);
+            check_selected(&list, k);
+
+            let mut list = input.clone();
+            assert_eq!(select_nth_linear(&mut list, k), expected);
+            check_selected(&list, k);
+        }
+    }
+
+    #[test]
+    fn select_many_duplicates() {
+        let mut input: Vec<i32> = (0..10000).map(|i| i % 3).collect();
+        assert_eq!(select_nth_unstable(&mut input, 5000), &1);
+        check_selected(&input, 5000);
+
+        let mut input = vec![4; 10000];
+        assert_eq!(select_nth_linear(&mut input, 9999), &4);
+    }
+
+    #[test]
+    fn select_by_key_desc() {
+        let mut input = vec![(1, 'c'), (2, 'a'), (3, 'b')];
+        assert_eq!(select_nth_unstable_by_key(&mut input, 0, |e| e.1), &(2, 'a'));
+        assert_eq!(select_nth_linear_by(&mut input, 0, |a, b| b.cmp(a)), &(3, 'b'));
+    }
+
+    #[test]
+    #[should_panic]
+    fn select_out_of_range() {
+        let mut input = vec![1, 2, 3];
+        select_nth_unstable(&mut input, 3);
+    }
+
+    #[test]
+    fn median_and_percentile() {
+        let mut input = vec![5, 1, 4, 2, 3, 6];
+        assert_eq!(median(&mut input), Some(&3));
+        assert_eq!(percentile(&mut input, 0.0), Some(&1));
+        assert_eq!(percentile(&mut input, 50.0), Some(&3));
+        assert_eq!(percentile(&mut input, 90.0), Some(&6));
+        assert_eq!(percentile(&mut input, 100.0), Some(&6));
+
+        let mut empty: Vec<i32> = vec![];
+        assert_eq!(median(&mut empty), None);
+        assert_eq!(percentile(&mut empty, 50.0), None);
+    }
+}
@@ -1,13 +1,34 @@
+use std::cmp::Ordering;
+use crate::sort::partial_compare;
+use crate::sort::probe::{swap, NoProbe, Probe};
+
 /// Selection sort: run time O(n^2), space - O(1)
-pub fn selection<T>(list: &mut [T]) where T: std::cmp::PartialOrd {
+pub fn selection<T>(list: &mut [T]) where T: PartialOrd {
+    selection_by(list, partial_compare);
+}
+
+/// Selection sort with a comparator function.
+pub fn selection_by<T, F>(list: &mut [T], compare: F) where F: FnMut(&T, &T) -> Ordering {
+    selection_probed(list, compare, &NoProbe);
+}
+
+/// Selection sort with a key extraction function.
+pub fn selection_by_key<T, K, F>(list: &mut [T], mut key: F) where F: FnMut(&T) -> K, K: PartialOrd {
+    selection_by(list, |a, b| partial_compare(&key(a), &key(b)));
+}
+
+pub(crate) fn selection_probed<T, F, P>(list: &mut [T], mut compare: F, probe: &P)
+    where F: FnMut(&T, &T) -> Ordering, P: Probe<T> + ?Sized {
     for i in 0..list.len() {
         let mut mini = i;
         for j in i+1..list.len() {
-            if list[j] < list[i] {
+            if compare(&list[j], &list[mini]) == Ordering::Less {
                 mini = j
             }
         }
-        list.swap(i, mini);
+        if mini != i {
+            swap(list, i, mini, probe);
+        }
     }
 }
 
@@ -51,4 +72,25 @@ mod tests {
         selection(&mut input);
         check_order(&input);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn selection_min_not_last_smaller() {
+        let mut input = vec![3, 1, 2];
+        selection(&mut input);
+        check_order(&input);
+    }
+
+    #[test]
+    fn selection_by_desc() {
+        let mut input = vec![1, 3, 2, 5, 4];
+        selection_by(&mut input, |a, b| b.cmp(a));
+        assert_eq!(input, vec![5, 4, 3, 2, 1]);
+    }
+
+    #[test]
+    fn selection_by_key_field() {
+        let mut input = vec![(1, 'c'), (2, 'a'), (3, 'b')];
+        selection_by_key(&mut input, |e| e.1);
+        assert_eq!(input, vec![(2, 'a'), (3, 'b'), (1, 'c')]);
+    }
+}
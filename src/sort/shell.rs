@@ -1,19 +1,134 @@
-/// Shell sort (improved insertion sort): run time O(n^2) average case, O(n) best case for sorted slices, space - O(1)
-pub fn shell<T>(list: &mut [T]) where T: std::cmp::PartialOrd {
-    let mut k = list.len()/2;
-    while k > 0 {
-        let mut i = 0;
-        while i < list.len() {
-            let mut j = i;
-            while j >= k {
-                if list[j] < list[j-k] {
-                    list.swap(j, j-k);
+use std::cmp::Ordering;
+use crate::sort::partial_compare;
+use crate::sort::probe::{swap, NoProbe, Probe};
+
+/// Gap sequence of the shell sort, the last gap is always 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GapSequence {
+    /// n/2, n/4, ..., 1 as originally proposed by Shell: O(n^2) worst case.
+    Shell,
+    /// 1, 4, 13, 40, ... (3h+1) up to n/3 by Knuth: O(n^(3/2)) worst case.
+    Knuth,
+    /// 1, 5, 19, 41, 109, ... by Sedgewick: O(n^(4/3)) worst case.
+    Sedgewick,
+    /// 1, 4, 10, 23, 57, 132, 301, 701 found empirically by Ciura, extended by a factor of 2.25.
+    Ciura,
+    /// 1, 4, 9, 20, 46, 103, ... (h' = 2.25h' + 1, rounded up) by Tokuda.
+    Tokuda,
+}
+
+impl GapSequence {
+    /// Gaps used for a list of n elements in decreasing order.
+    pub fn gaps(self, n: usize) -> Vec<usize> {
+        // gaps are collected in increasing order
+        let mut gaps = Vec::new();
+        match self {
+            GapSequence::Shell => {
+                let mut h = n/2;
+                while h > 0 {
+                    gaps.push(h);
+                    h /= 2;
+                }
+                gaps.reverse();
+            }
+            GapSequence::Knuth => {
+                let mut h = 1;
+                while h < n {
+                    gaps.push(h);
+                    if h >= n/3 {
+                        break;
+                    }
+                    h = 3*h + 1;
+                }
+            }
+            GapSequence::Sedgewick => {
+                // 9*(4^k - 2^k) + 1 interleaved with 4^(k+2) - 3*2^(k+2) + 1
+                for k in 0.. {
+                    let p = 1u128 << k;
+                    let a = 9 * (p*p - p) + 1;
+                    let b = 16 * p*p - 12 * p + 1;
+                    if a >= n as u128 {
+                        break;
+                    }
+                    gaps.push(a as usize);
+                    if b < n as u128 {
+                        gaps.push(b as usize);
+                    }
+                }
+            }
+            GapSequence::Ciura => {
+                let known = [1, 4, 10, 23, 57, 132, 301, 701];
+                gaps.extend(known.iter().cloned().take_while(|&g| g < n));
+                if gaps.len() == known.len() {
+                    let mut h = (701.0 * 2.25f64).floor() as usize;
+                    while h < n {
+                        gaps.push(h);
+                        h = (h as f64 * 2.25).floor() as usize;
+                    }
+                }
+            }
+            GapSequence::Tokuda => {
+                let mut h: f64 = 1.0;
+                while (h.ceil() as usize) < n {
+                    gaps.push(h.ceil() as usize);
+                    h = 2.25 * h + 1.0;
                 }
-                j -= k;
             }
-            i += k;
         }
-        k /= 2;
+        gaps.reverse();
+        gaps
+    }
+}
+
+/// Shell sort (improved insertion sort) with Ciura's gaps: insertion sort over elements h apart for
+/// decreasing gaps h. Run time depends on the gap sequence, about O(n^(4/3)) average case and O(n) for
+/// sorted slices, space - O(1)
+pub fn shell<T>(list: &mut [T]) where T: PartialOrd {
+    shell_by(list, partial_compare);
+}
+
+/// Shell sort with a comparator function.
+pub fn shell_by<T, F>(list: &mut [T], compare: F) where F: FnMut(&T, &T) -> Ordering {
+    shell_probed(list, GapSequence::Ciura, compare, &NoProbe);
+}
+
+/// Shell sort with a key extraction function.
+pub fn shell_by_key<T, K, F>(list: &mut [T], mut key: F) where F: FnMut(&T) -> K, K: PartialOrd {
+    shell_by(list, |a, b| partial_compare(&key(a), &key(b)));
+}
+
+/// Shell sort with the given gap sequence.
+pub fn shell_with_gaps<T>(list: &mut [T], gaps: GapSequence) where T: PartialOrd {
+    shell_by_with_gaps(list, gaps, partial_compare);
+}
+
+/// Shell sort with the given gap sequence and a comparator function.
+pub fn shell_by_with_gaps<T, F>(list: &mut [T], gaps: GapSequence, compare: F) where F: FnMut(&T, &T) -> Ordering {
+    shell_probed(list, gaps, compare, &NoProbe);
+}
+
+/// Checks that every subsequence of elements h apart is in non-decreasing order.
+pub fn is_h_sorted<T>(list: &[T], h: usize) -> bool where T: PartialOrd {
+    assert!(h > 0, "gap must be positive");
+    (h..list.len()).all(|i| partial_compare(&list[i], &list[i-h]) != Ordering::Less)
+}
+
+pub(crate) fn shell_probed<T, F, P>(list: &mut [T], gaps: GapSequence, mut compare: F, probe: &P)
+    where F: FnMut(&T, &T) -> Ordering, P: Probe<T> + ?Sized {
+    for h in gaps.gaps(list.len()) {
+        h_sort(list, h, &mut compare, probe);
+    }
+}
+
+// Insertion sort of every subsequence of elements h apart.
+fn h_sort<T, F, P>(list: &mut [T], h: usize, compare: &mut F, probe: &P)
+    where F: FnMut(&T, &T) -> Ordering, P: Probe<T> + ?Sized {
+    for i in h..list.len() {
+        let mut j = i;
+        while j >= h && compare(&list[j], &list[j-h]) == Ordering::Less {
+            swap(list, j, j-h, probe);
+            j -= h;
+        }
     }
 }
 
@@ -55,5 +170,63 @@ mod tests {
         let mut input = vec![5, 4, 3, 2, 1];
         shell(&mut input);
         check_order(&input);
-    }    
-}
\ No newline at end of file
+    }
+
+    #[test]
+    fn shell_by_desc() {
+        let mut input = vec![1, 3, 2, 5, 4];
+        shell_by(&mut input, |a, b| b.cmp(a));
+        assert_eq!(input, vec![5, 4, 3, 2, 1]);
+    }
+
+    #[test]
+    fn shell_by_key_field() {
+        let mut input = vec![(1, 'c'), (2, 'a'), (3, 'b')];
+        shell_by_key(&mut input, |e| e.1);
+        assert_eq!(input, vec![(2, 'a'), (3, 'b'), (1, 'c')]);
+    }
+
+    #[test]
+    fn shell_gap_sequences() {
+        assert_eq!(GapSequence::Shell.gaps(20), vec![10, 5, 2, 1]);
+        assert_eq!(GapSequence::Knuth.gaps(1000), vec![364, 121, 40, 13, 4, 1]);
+        assert_eq!(GapSequence::Sedgewick.gaps(1000), vec![929, 505, 209, 109, 41, 19, 5, 1]);
+        assert_eq!(GapSequence::Ciura.gaps(100), vec![57, 23, 10, 4, 1]);
+        assert_eq!(GapSequence::Ciura.gaps(5000), vec![3548, 1577, 701, 301, 132, 57, 23, 10, 4, 1]);
+        assert_eq!(GapSequence::Tokuda.gaps(1000), vec![525, 233, 103, 46, 20, 9, 4, 1]);
+        assert!(GapSequence::Ciura.gaps(1).is_empty());
+        assert_eq!(GapSequence::Knuth.gaps(2), vec![1]);
+    }
+
+    #[test]
+    fn shell_every_sequence() {
+        let sequences = [GapSequence::Shell, GapSequence::Knuth, GapSequence::Sedgewick, GapSequence::Ciura, GapSequence::Tokuda];
+        let input: Vec<i32> = (0..3000).map(|i| (i * 7919) % 3001).collect();
+        let mut expected = input.clone();
+        expected.sort();
+        for &gaps in sequences.iter() {
+            let mut list = input.clone();
+            shell_with_gaps(&mut list, gaps);
+            assert_eq!(list, expected, "{:?}", gaps);
+        }
+    }
+
+    #[test]
+    fn shell_h_sorts_every_subsequence() {
+        let mut list: Vec<i32> = (0..500).rev().collect();
+        for &h in GapSequence::Knuth.gaps(list.len()).iter() {
+            assert!(!is_h_sorted(&list, h));
+            h_sort(&mut list, h, &mut |a: &i32, b: &i32| a.cmp(b), &NoProbe);
+            assert!(is_h_sorted(&list, h), "gap {}", h);
+        }
+        assert!(is_h_sorted(&list, 1));
+    }
+
+    #[test]
+    fn is_h_sorted_interleaved() {
+        let list = vec![1, 5, 2, 6, 3, 7];
+        assert!(is_h_sorted(&list, 2));
+        assert!(!is_h_sorted(&list, 1));
+        assert!(is_h_sorted(&list, 10));
+    }
+}
@@ -0,0 +1,575 @@
+use std::fmt;
+
+use crate::sort;
+use crate::sort::partial_compare;
+use crate::sort::probe::{probed, Probe};
+
+/// Asymptotic bound used to describe run time and memory usage of an algorithm.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Complexity {
+    Constant,
+    Logarithmic,
+    Linear,
+    Linearithmic,
+    Quadratic,
+}
+
+impl fmt::Display for Complexity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            Complexity::Constant => "O(1)",
+            Complexity::Logarithmic => "O(logn)",
+            Complexity::Linear => "O(n)",
+            Complexity::Linearithmic => "O(n*logn)",
+            Complexity::Quadratic => "O(n^2)",
+        };
+        f.write_str(s)
+    }
+}
+
+/// Metadata describing a sorting algorithm.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Properties {
+    /// Equal elements keep their relative order.
+    pub stable: bool,
+    /// Elements are rearranged inside the slice without copying them out.
+    pub in_place: bool,
+    /// Worst case run time.
+    pub worst_case: Complexity,
+    /// Worst case extra memory, including the recursion stack.
+    pub extra_memory: Complexity,
+}
+
+/// Common interface of the sorting algorithms, so they can be passed around and picked at runtime.
+pub trait Sorter<T> {
+    /// Name the algorithm is registered under, e.g. `"quick_3_way"`.
+    fn name(&self) -> &'static str;
+
+    fn properties(&self) -> Properties;
+
+    /// Sorts the slice in non-decreasing order.
+    fn sort(&self, list: &mut [T]);
+
+    /// Sorts the slice reporting the compared, swapped and written elements to the probe.
+    /// The default implementation calls `sort` and reports nothing, so custom algorithms only
+    /// show up with zero operations in `stats::measure` and `trace::record` unless they override it.
+    fn sort_probed(&self, list: &mut [T], _probe: &dyn Probe<T>) {
+        self.sort(list);
+    }
+}
+
+pub struct BubbleSort;
+pub struct SelectionSort;
+pub struct InsertionSort;
+pub struct ShellSort;
+pub struct MergeSort;
+pub struct BottomUpMergeSort;
+pub struct NaturalMergeSort;
+pub struct TimSort;
+pub struct QuickSort;
+pub struct Quick3WaySort;
+pub struct HeapSort;
+pub struct IntroSort;
+pub struct PdqSort;
+pub struct FlagSort;
+
+impl<T> Sorter<T> for BubbleSort where T: PartialOrd {
+    fn name(&self) -> &'static str {
+        "bubble"
+    }
+
+    fn properties(&self) -> Properties {
+        Properties {
+            stable: true,
+            in_place: true,
+            worst_case: Complexity::Quadratic,
+            extra_memory: Complexity::Constant,
+        }
+    }
+
+    fn sort(&self, list: &mut [T]) {
+        sort::bubble(list);
+    }
+
+    fn sort_probed(&self, list: &mut [T], probe: &dyn Probe<T>) {
+        sort::bubble::bubble_probed(list, probed(partial_compare, probe), probe);
+    }
+}
+
+impl<T> Sorter<T> for SelectionSort where T: PartialOrd {
+    fn name(&self) -> &'static str {
+        "selection"
+    }
+
+    fn properties(&self) -> Properties {
+        Properties {
+            stable: false,
+            in_place: true,
+            worst_case: Complexity::Quadratic,
+            extra_memory: Complexity::Constant,
+        }
+    }
+
+    fn sort(&self, list: &mut [T]) {
+        sort::selection(list);
+    }
+
+    fn sort_probed(&self, list: &mut [T], probe: &dyn Probe<T>) {
+        sort::selection::selection_probed(list, probed(partial_compare, probe), probe);
+    }
+}
+
+impl<T> Sorter<T> for InsertionSort where T: PartialOrd {
+    fn name(&self) -> &'static str {
+        "insertion"
+    }
+
+    fn properties(&self) -> Properties {
+        Properties {
+            stable: true,
+            in_place: true,
+            worst_case: Complexity::Quadratic,
+            extra_memory: Complexity::Constant,
+        }
+    }
+
+    fn sort(&self, list: &mut [T]) {
+        sort::insertion(list);
+    }
+
+    fn sort_probed(&self, list: &mut [T], probe: &dyn Probe<T>) {
+        sort::insertion::insertion_probed(list, probed(partial_compare, probe), probe);
+    }
+}
+
+impl<T> Sorter<T> for ShellSort where T: PartialOrd {
+    fn name(&self) -> &'static str {
+        "shell"
+    }
+
+    fn properties(&self) -> Properties {
+        Properties {
+            stable: false,
+            in_place: true,
+            worst_case: Complexity::Quadratic,
+            extra_memory: Complexity::Constant,
+        }
+    }
+
+    fn sort(&self, list: &mut [T]) {
+        sort::shell(list);
+    }
+
+    fn sort_probed(&self, list: &mut [T], probe: &dyn Probe<T>) {
+        sort::shell::shell_probed(list, sort::GapSequence::Ciura, probed(partial_compare, probe), probe);
+    }
+}
+
+impl<T> Sorter<T> for MergeSort where T: PartialOrd {
+    fn name(&self) -> &'static str {
+        "merge"
+    }
+
+    fn properties(&self) -> Properties {
+        Properties {
+            stable: true,
+            in_place: false,
+            worst_case: Complexity::Linearithmic,
+            extra_memory: Complexity::Linear,
+        }
+    }
+
+    fn sort(&self, list: &mut [T]) {
+        sort::merge(list);
+    }
+
+    fn sort_probed(&self, list: &mut [T], probe: &dyn Probe<T>) {
+        sort::merge::merge_probed(list, &mut Vec::new(), probed(partial_compare, probe), probe);
+    }
+}
+
+impl<T> Sorter<T> for BottomUpMergeSort where T: PartialOrd {
+    fn name(&self) -> &'static str {
+        "merge_bottom_up"
+    }
+
+    fn properties(&self) -> Properties {
+        Properties {
+            stable: true,
+            in_place: false,
+            worst_case: Complexity::Linearithmic,
+            extra_memory: Complexity::Linear,
+        }
+    }
+
+    fn sort(&self, list: &mut [T]) {
+        sort::merge_bottom_up(list);
+    }
+
+    fn sort_probed(&self, list: &mut [T], probe: &dyn Probe<T>) {
+        sort::merge_bottom_up::merge_bottom_up_probed(list, probed(partial_compare, probe), probe);
+    }
+}
+
+impl<T> Sorter<T> for NaturalMergeSort where T: PartialOrd {
+    fn name(&self) -> &'static str {
+        "merge_natural"
+    }
+
+    fn properties(&self) -> Properties {
+        Properties {
+            stable: true,
+            in_place: false,
+            worst_case: Complexity::Linearithmic,
+            extra_memory: Complexity::Linear,
+        }
+    }
+
+    fn sort(&self, list: &mut [T]) {
+        sort::merge_natural(list);
+    }
+
+    fn sort_probed(&self, list: &mut [T], probe: &dyn Probe<T>) {
+        sort::merge_natural::merge_natural_probed(list, probed(partial_compare, probe), probe);
+    }
+}
+
+impl<T> Sorter<T> for TimSort where T: PartialOrd {
+    fn name(&self) -> &'static str {
+        "tim"
+    }
+
+    fn properties(&self) -> Properties {
+        Properties {
+            stable: true,
+            in_place: false,
+            worst_case: Complexity::Linearithmic,
+            extra_memory: Complexity::Linear,
+        }
+    }
+
+    fn sort(&self, list: &mut [T]) {
+        sort::tim(list);
+    }
+
+    fn sort_probed(&self, list: &mut [T], probe: &dyn Probe<T>) {
+        sort::tim::tim_probed(list, probed(partial_compare, probe), probe);
+    }
+}
+
+impl<T> Sorter<T> for QuickSort where T: PartialOrd {
+    fn name(&self) -> &'static str {
+        "quick"
+    }
+
+    fn properties(&self) -> Properties {
+        Properties {
+            stable: false,
+            in_place: true,
+            worst_case: Complexity::Quadratic,
+            extra_memory: Complexity::Linear,
+        }
+    }
+
+    fn sort(&self, list: &mut [T]) {
+        sort::quick(list);
+    }
+
+    fn sort_probed(&self, list: &mut [T], probe: &dyn Probe<T>) {
+        sort::quick::quick_probed(list, &mut rand::thread_rng(), probed(partial_compare, probe), probe);
+    }
+}
+
+impl<T> Sorter<T> for Quick3WaySort where T: PartialOrd {
+    fn name(&self) -> &'static str {
+        "quick_3_way"
+    }
+
+    fn properties(&self) -> Properties {
+        Properties {
+            stable: false,
+            in_place: true,
+            worst_case: Complexity::Quadratic,
+            extra_memory: Complexity::Linear,
+        }
+    }
+
+    fn sort(&self, list: &mut [T]) {
+        sort::quick_3_way(list);
+    }
+
+    fn sort_probed(&self, list: &mut [T], probe: &dyn Probe<T>) {
+        sort::quick_3_way::quick_3_way_probed(list, probed(partial_compare, probe), probe);
+    }
+}
+
+impl<T> Sorter<T> for HeapSort where T: PartialOrd {
+    fn name(&self) -> &'static str {
+        "heap"
+    }
+
+    fn properties(&self) -> Properties {
+        Properties {
+            stable: false,
+            in_place: true,
+            worst_case: Complexity::Linearithmic,
+            extra_memory: Complexity::Constant,
+        }
+    }
+
+    fn sort(&self, list: &mut [T]) {
+        sort::heap(list);
+    }
+
+    fn sort_probed(&self, list: &mut [T], probe: &dyn Probe<T>) {
+        sort::heap::heap_probed(list, probed(partial_compare, probe), probe);
+    }
+}
+
+impl<T> Sorter<T> for IntroSort where T: PartialOrd {
+    fn name(&self) -> &'static str {
+        "intro"
+    }
+
+    fn properties(&self) -> Properties {
+        Properties {
+            stable: false,
+            in_place: true,
+            worst_case: Complexity::Linearithmic,
+            extra_memory: Complexity::Logarithmic,
+        }
+    }
+
+    fn sort(&self, list: &mut [T]) {
+        sort::intro(list);
+    }
+
+    fn sort_probed(&self, list: &mut [T], probe: &dyn Probe<T>) {
+        sort::intro::intro_probed(list, probed(partial_compare, probe), probe);
+    }
+}
+
+impl<T> Sorter<T> for PdqSort where T: PartialOrd {
+    fn name(&self) -> &'static str {
+        "pdq"
+    }
+
+    fn properties(&self) -> Properties {
+        Properties {
+            stable: false,
+            in_place: true,
+            worst_case: Complexity::Linearithmic,
+            extra_memory: Complexity::Logarithmic,
+        }
+    }
+
+    fn sort(&self, list: &mut [T]) {
+        sort::pdq(list);
+    }
+
+    fn sort_probed(&self, list: &mut [T], probe: &dyn Probe<T>) {
+        sort::pdq::pdq_probed(list, probed(partial_compare, probe), probe);
+    }
+}
+
+impl Sorter<i32> for FlagSort {
+    fn name(&self) -> &'static str {
+        "flag"
+    }
+
+    fn properties(&self) -> Properties {
+        Properties {
+            stable: false,
+            in_place: true,
+            worst_case: Complexity::Linear,
+            extra_memory: Complexity::Constant,
+        }
+    }
+
+    fn sort(&self, list: &mut [i32]) {
+        sort::flag(list);
+    }
+
+    fn sort_probed(&self, list: &mut [i32], probe: &dyn Probe<i32>) {
+        sort::flag::flag_probed(list, probe);
+    }
+}
+
+/// Collection of sorting algorithms looked up by name.
+pub struct Registry<T> {
+    sorters: Vec<Box<dyn Sorter<T>>>,
+}
+
+impl<T> Registry<T> {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Registry {
+            sorters: Vec::new(),
+        }
+    }
+
+    /// Adds an algorithm, replacing a previously registered one with the same name.
+    pub fn register(&mut self, sorter: Box<dyn Sorter<T>>) {
+        match self.sorters.iter().position(|s| s.name() == sorter.name()) {
+            Some(i) => self.sorters[i] = sorter,
+            None => self.sorters.push(sorter),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn Sorter<T>> {
+        self.sorters.iter()
+            .find(|s| s.name() == name)
+            .map(|s| s.as_ref())
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        self.sorters.iter().map(|s| s.name()).collect()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &dyn Sorter<T>> {
+        self.sorters.iter().map(|s| s.as_ref())
+    }
+
+    pub fn len(&self) -> usize {
+        self.sorters.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sorters.is_empty()
+    }
+}
+
+impl<T> Registry<T> where T: PartialOrd + 'static {
+    /// Registry with every general purpose algorithm of the `sort` module.
+    /// `FlagSort` only handles `i32` values {0, 1, 2} and has to be registered explicitly.
+    pub fn standard() -> Self {
+        let mut r = Registry::new();
+        r.register(Box::new(BubbleSort));
+        r.register(Box::new(SelectionSort));
+        r.register(Box::new(InsertionSort));
+        r.register(Box::new(ShellSort));
+        r.register(Box::new(MergeSort));
+        r.register(Box::new(BottomUpMergeSort));
+        r.register(Box::new(NaturalMergeSort));
+        r.register(Box::new(TimSort));
+        r.register(Box::new(QuickSort));
+        r.register(Box::new(Quick3WaySort));
+        r.register(Box::new(HeapSort));
+        r.register(Box::new(IntroSort));
+        r.register(Box::new(PdqSort));
+        r
+    }
+}
+
+impl<T> Default for Registry<T> {
+    fn default() -> Self {
+        Registry::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check_order<T>(list: &[T]) where T: PartialOrd + std::fmt::Debug {
+        let mut prev: &T = &list[0];
+        for a in list.iter() {
+            assert!(a >= prev, "non-decreasing order violation: {:?}", list);
+            prev = a;
+        }
+    }
+
+    #[test]
+    fn standard_names() {
+        let r: Registry<i32> = Registry::standard();
+        assert_eq!(r.names(), vec!["bubble", "selection", "insertion", "shell", "merge", "merge_bottom_up", "merge_natural", "tim", "quick", "quick_3_way", "heap", "intro", "pdq"]);
+    }
+
+    #[test]
+    fn get_by_name() {
+        let r: Registry<i32> = Registry::standard();
+        let s = r.get("quick_3_way").unwrap();
+        assert_eq!(s.name(), "quick_3_way");
+        assert!(r.get("bogo").is_none());
+    }
+
+    #[test]
+    fn all_standard_sort() {
+        let r = Registry::standard();
+        for s in r.iter() {
+            let mut input = vec![5, 1, 4, 2, 2, 3, 1, 0];
+            s.sort(&mut input);
+            check_order(&input);
+        }
+    }
+
+    #[test]
+    fn standard_strings() {
+        let r: Registry<String> = Registry::standard();
+        for s in r.iter() {
+            let mut input: Vec<String> = vec!["pear", "apple", "fig", "banana", "apple"]
+                .into_iter().map(String::from).collect();
+            s.sort(&mut input);
+            assert_eq!(input, vec!["apple", "apple", "banana", "fig", "pear"], "{}", s.name());
+        }
+    }
+
+    #[test]
+    fn register_flag() {
+        let mut r = Registry::standard();
+        r.register(Box::new(FlagSort));
+        assert_eq!(r.len(), 14);
+
+        let mut input = vec![2, 1, 0, 2, 1, 0];
+        r.get("flag").unwrap().sort(&mut input);
+        check_order(&input);
+    }
+
+    #[test]
+    fn custom_sorter_without_probe() {
+        struct StdSort;
+
+        impl Sorter<i32> for StdSort {
+            fn name(&self) -> &'static str {
+                "std"
+            }
+
+            fn properties(&self) -> Properties {
+                Properties {
+                    stable: true,
+                    in_place: false,
+                    worst_case: Complexity::Linearithmic,
+                    extra_memory: Complexity::Linear,
+                }
+            }
+
+            fn sort(&self, list: &mut [i32]) {
+                list.sort();
+            }
+        }
+
+        let mut r: Registry<i32> = Registry::new();
+        r.register(Box::new(StdSort));
+        let mut input = vec![3, 1, 2];
+        let stats = sort::stats::measure(r.get("std").unwrap(), &mut input);
+        assert_eq!(input, vec![1, 2, 3]);
+        assert_eq!(stats, sort::stats::SortStats::default());
+    }
+
+    #[test]
+    fn register_replaces_same_name() {
+        let mut r: Registry<i32> = Registry::new();
+        r.register(Box::new(QuickSort));
+        r.register(Box::new(QuickSort));
+        assert_eq!(r.len(), 1);
+    }
+
+    #[test]
+    fn properties() {
+        let p = Sorter::<i32>::properties(&HeapSort);
+        assert!(p.in_place);
+        assert!(!p.stable);
+        assert_eq!(p.worst_case, Complexity::Linearithmic);
+        assert_eq!(p.extra_memory.to_string(), "O(1)");
+    }
+}
@@ -0,0 +1,145 @@
+//! Instrumented sorting: runs an algorithm of the registry with a counting probe and reports
+//! the number of operations it made.
+use std::cell::Cell;
+use crate::sort::probe::Probe;
+use crate::sort::sorter::Sorter;
+
+/// Operations made by one run of a sort.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SortStats {
+    /// Calls of the comparator.
+    pub comparisons: usize,
+    /// Exchanges of two elements of the slice.
+    pub swaps: usize,
+    /// Elements moved into a slice position from auxiliary memory, by merges or by rotations.
+    pub writes: usize,
+    /// Deepest nesting of recursive calls, 0 for iterative algorithms.
+    pub max_recursion_depth: usize,
+}
+
+/// Sorts the list with the algorithm and returns the operations it made.
+pub fn measure<T>(sorter: &dyn Sorter<T>, list: &mut [T]) -> SortStats {
+    let counter = Counter::default();
+    sorter.sort_probed(list, &counter);
+    counter.stats.get()
+}
+
+#[derive(Default)]
+struct Counter {
+    stats: Cell<SortStats>,
+    depth: Cell<usize>,
+}
+
+impl Counter {
+    fn update<F>(&self, f: F) where F: FnOnce(&mut SortStats) {
+        let mut stats = self.stats.get();
+        f(&mut stats);
+        self.stats.set(stats);
+    }
+}
+
+impl<T> Probe<T> for Counter {
+    fn compare(&self, _a: &T, _b: &T) {
+        self.update(|s| s.comparisons += 1);
+    }
+
+    fn swap(&self, _a: &T, _b: &T) {
+        self.update(|s| s.swaps += 1);
+    }
+
+    fn write(&self, _dst: &T) {
+        self.update(|s| s.writes += 1);
+    }
+
+    fn enter(&self) {
+        let depth = self.depth.get() + 1;
+        self.depth.set(depth);
+        self.update(|s| s.max_recursion_depth = s.max_recursion_depth.max(depth));
+    }
+
+    fn leave(&self) {
+        self.depth.set(self.depth.get() - 1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sort::sorter::*;
+
+    fn input(n: usize) -> Vec<u32> {
+        (0..n).map(|i| ((i as u64 * 2654435761) % 1000003) as u32).collect()
+    }
+
+    fn inversions(list: &[u32]) -> usize {
+        let mut count = 0;
+        for i in 0..list.len() {
+            count += list[i+1..].iter().filter(|&&e| e < list[i]).count();
+        }
+        count
+    }
+
+    #[test]
+    fn insertion_quarter_n_squared() {
+        let n = 1000;
+        let mut list = input(n);
+        let inv = inversions(&list);
+        let stats = measure(&InsertionSort, &mut list);
+
+        // every swap fixes one inversion, every element but the first takes at most one extra comparison
+        assert_eq!(stats.swaps, inv);
+        assert!(stats.comparisons >= inv && stats.comparisons < inv + n, "{:?}", stats);
+        let quarter = n * n / 4;
+        assert!(stats.comparisons > quarter * 9 / 10 && stats.comparisons < quarter * 11 / 10, "{:?}", stats);
+        assert_eq!(stats.writes, 0);
+        assert_eq!(stats.max_recursion_depth, 0);
+    }
+
+    #[test]
+    fn presorted_insertion_and_bubble() {
+        let mut list: Vec<u32> = (0..100).collect();
+        let stats = measure(&InsertionSort, &mut list);
+        assert_eq!(stats, SortStats { comparisons: 99, ..SortStats::default() });
+
+        let stats = measure(&BubbleSort, &mut list);
+        assert_eq!(stats, SortStats { comparisons: 99, ..SortStats::default() });
+    }
+
+    #[test]
+    fn quick_3_way_wins_on_duplicates() {
+        let mut list: Vec<u32> = input(5000).into_iter().map(|e| e % 4).collect();
+        let three_way = measure(&Quick3WaySort, &mut list.clone());
+        let quick = measure(&QuickSort, &mut list);
+        assert!(three_way.comparisons * 10 < quick.comparisons, "{:?} vs {:?}", three_way, quick);
+        assert!(three_way.max_recursion_depth < quick.max_recursion_depth);
+    }
+
+    #[test]
+    fn merge_writes_and_depth() {
+        let mut list = input(1000);
+        let stats = measure(&MergeSort, &mut list);
+        assert_eq!(stats.max_recursion_depth, 10);
+        assert!(stats.writes > 0);
+        assert!(stats.comparisons < 1000 * 10);
+
+        let stats = measure(&HeapSort, &mut input(1000));
+        assert_eq!(stats.writes, 0);
+        assert_eq!(stats.max_recursion_depth, 0);
+    }
+
+    #[test]
+    fn every_registered_sort_counts() {
+        let r: Registry<u32> = Registry::standard();
+        for s in r.iter() {
+            let mut list = input(300);
+            let stats = measure(s, &mut list);
+            assert!(list.windows(2).all(|w| w[0] <= w[1]), "{}", s.name());
+            assert!(stats.comparisons >= 299, "{}: {:?}", s.name(), stats);
+            assert!(stats.swaps + stats.writes > 0, "{}: {:?}", s.name(), stats);
+        }
+
+        let mut list: Vec<i32> = (0..300).map(|i| i % 3).collect();
+        let stats = measure(&FlagSort, &mut list);
+        assert_eq!(stats.comparisons, 300);
+    }
+}
@@ -0,0 +1,214 @@
+// Ranges of this size or smaller are finished with insertion sort on the remaining suffixes.
+const CUTOFF: usize = 16;
+// One bucket for strings ending at the current position plus one per byte value.
+const BUCKETS: usize = 257;
+
+/// MSD radix sort of strings in byte-wise lexicographic order, which for UTF-8 matches `str` ordering.
+/// Works on `String`, `&str`, `Vec<u8>` or any `AsRef<[u8]>`. Characters of a shared prefix are
+/// examined once: run time O(n*w) worst case for w-byte strings, space - O(w) for the recursion.
+pub fn msd_radix<T>(list: &mut [T]) where T: AsRef<[u8]> {
+    msd_internal(list, 0);
+}
+
+/// 3-way radix quick sort of strings: partitions on the byte at position d into less, equal and greater
+/// parts and continues with the equal part at position d+1. Handles long common prefixes and
+/// duplicate keys well: run time O(n*logn + w*n) average case, space - O(logn + w).
+pub fn quick_3_way_radix<T>(list: &mut [T]) where T: AsRef<[u8]> {
+    quick_internal(list, 0);
+}
+
+// Bucket of the string at position d: 0 if the string ends before d, byte value + 1 otherwise.
+fn bucket<T>(s: &T, d: usize) -> usize where T: AsRef<[u8]> {
+    match s.as_ref().get(d) {
+        Some(&b) => b as usize + 1,
+        None => 0,
+    }
+}
+
+// Insertion sort of strings sharing their first d bytes, comparing only the remaining suffixes.
+fn insertion<T>(list: &mut [T], d: usize) where T: AsRef<[u8]> {
+    for i in 1..list.len() {
+        for j in (1..i+1).rev() {
+            if list[j].as_ref()[d..] >= list[j-1].as_ref()[d..] {
+                break;
+            }
+            list.swap(j, j-1);
+        }
+    }
+}
+
+// Sorts strings sharing their first d bytes.
+fn msd_internal<T>(list: &mut [T], mut d: usize) where T: AsRef<[u8]> {
+    let mut count = vec![0usize; BUCKETS];
+    loop {
+        if list.len() <= CUTOFF {
+            insertion(list, d);
+            return;
+        }
+
+        for c in count.iter_mut() {
+            *c = 0;
+        }
+        for s in list.iter() {
+            count[bucket(s, d)] += 1;
+        }
+
+        if count[0] == list.len() {
+            // all strings end here and are equal
+            return;
+        }
+        if count[1..].contains(&list.len()) {
+            // a common byte, moving on to the next position without recursion
+            d += 1;
+            continue;
+        }
+        break;
+    }
+
+    // in-place distribution into buckets: next[b] is the first unsorted position of bucket b
+    let mut start = vec![0usize; BUCKETS + 1];
+    for b in 0..BUCKETS {
+        start[b+1] = start[b] + count[b];
+    }
+    let mut next = start.clone();
+    for b in 0..BUCKETS {
+        while next[b] < start[b+1] {
+            let x = bucket(&list[next[b]], d);
+            if x == b {
+                next[b] += 1;
+            } else {
+                list.swap(next[b], next[x]);
+                next[x] += 1;
+            }
+        }
+    }
+
+    // strings ending at d (bucket 0) are equal to each other
+    for b in 1..BUCKETS {
+        if start[b+1] - start[b] > 1 {
+            msd_internal(&mut list[start[b]..start[b+1]], d + 1);
+        }
+    }
+}
+
+// Sorts strings sharing their first d bytes.
+fn quick_internal<T>(mut list: &mut [T], mut d: usize) where T: AsRef<[u8]> {
+    loop {
+        if list.len() <= CUTOFF {
+            insertion(list, d);
+            return;
+        }
+
+        // the middle element as pivot avoids quadratic behaviour on sorted input
+        let mid = list.len()/2;
+        list.swap(0, mid);
+        let v = bucket(&list[0], d);
+
+        let mut l = 0;
+        let mut g = list.len()-1;
+        let mut i = 1;
+        while i <= g {
+            let t = bucket(&list[i], d);
+            if t < v {
+                list.swap(l, i);
+                l += 1;
+                i += 1;
+            } else if t > v {
+                list.swap(i, g);
+                g -= 1;
+            } else {
+                i += 1;
+            }
+        }
+
+        // list[l..g+1] share the byte at d
+        let rest = list;
+        let (less, rest) = rest.split_at_mut(l);
+        let (equal, greater) = rest.split_at_mut(g + 1 - l);
+        quick_internal(less, d);
+        quick_internal(greater, d);
+        if v == 0 {
+            // strings ending at d are equal
+            return;
+        }
+        list = equal;
+        d += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check_order<T>(list: &[T]) where T: PartialOrd + std::fmt::Debug {
+        let mut prev: &T = &list[0];
+        for a in list.iter() {
+            assert!(a >= prev, "non-decreasing order violation: {:?}", list);
+            prev = a;
+        }
+    }
+
+    fn words() -> Vec<String> {
+        let base = ["she", "sells", "seashells", "by", "the", "sea", "shore", "the", "shells", "she",
+            "sells", "are", "surely", "seashells", "", "s", "été", "ete", "zebra", "a"];
+        (0..60).map(|i| format!("{}{}", base[(i * 7) % base.len()], if i % 3 == 0 { "" } else { "x" })).collect()
+    }
+
+    fn urls() -> Vec<String> {
+        (0..300).map(|i| format!("https://example.com/static/assets/images/{}/{}.png", (i * 37) % 11, (i * 101) % 97)).collect()
+    }
+
+    #[test]
+    fn msd_strings() {
+        for input in [words(), urls()].iter() {
+            let mut list = input.clone();
+            msd_radix(&mut list);
+            let mut expected = input.clone();
+            expected.sort();
+            assert_eq!(list, expected);
+        }
+    }
+
+    #[test]
+    fn quick_3_way_radix_strings() {
+        for input in [words(), urls()].iter() {
+            let mut list = input.clone();
+            quick_3_way_radix(&mut list);
+            let mut expected = input.clone();
+            expected.sort();
+            assert_eq!(list, expected);
+        }
+    }
+
+    #[test]
+    fn string_slices_and_bytes() {
+        let owned = urls();
+        let mut strs: Vec<&str> = owned.iter().map(|s| s.as_str()).collect();
+        msd_radix(&mut strs);
+        check_order(&strs);
+
+        let mut strs: Vec<&str> = owned.iter().map(|s| s.as_str()).collect();
+        quick_3_way_radix(&mut strs);
+        check_order(&strs);
+
+        let mut bytes: Vec<Vec<u8>> = vec![vec![2, 0], vec![], vec![1, 255, 3], vec![1, 255], vec![0]];
+        msd_radix(&mut bytes);
+        assert_eq!(bytes, vec![vec![], vec![0], vec![1, 255], vec![1, 255, 3], vec![2, 0]]);
+    }
+
+    #[test]
+    fn string_duplicates_and_empty() {
+        let mut input = vec![String::new(); 40];
+        input.extend(vec![String::from("same"); 40]);
+        input.reverse();
+        let mut list = input.clone();
+        msd_radix(&mut list);
+        check_order(&list);
+        quick_3_way_radix(&mut input);
+        check_order(&input);
+
+        let mut empty: Vec<String> = vec![];
+        msd_radix(&mut empty);
+        quick_3_way_radix(&mut empty);
+    }
+}
@@ -0,0 +1,447 @@
+use std::cmp::Ordering;
+use std::mem::MaybeUninit;
+use std::ptr;
+use crate::sort::partial_compare;
+use crate::sort::merge::{scratch, MergeHole};
+use crate::sort::merge_natural::count_run;
+use crate::sort::probe::{written, NoProbe, Probe};
+
+// Lists shorter than this are sorted with binary insertion sort only.
+const MIN_MERGE: usize = 64;
+// Initial number of consecutive wins of one run before a merge switches to galloping.
+const MIN_GALLOP: usize = 7;
+
+/// Timsort: natural merge sort extending short runs with binary insertion sort and merging them
+/// with galloping. Run time O(n*logn) worst case, O(n) for presorted slices, space - O(n), stable.
+pub fn tim<T>(list: &mut [T]) where T: PartialOrd {
+    tim_by(list, partial_compare);
+}
+
+/// Timsort with a comparator function.
+pub fn tim_by<T, F>(list: &mut [T], compare: F) where F: FnMut(&T, &T) -> Ordering {
+    tim_probed(list, compare, &NoProbe);
+}
+
+/// Timsort with a key extraction function.
+pub fn tim_by_key<T, K, F>(list: &mut [T], mut key: F) where F: FnMut(&T) -> K, K: PartialOrd {
+    tim_by(list, |a, b| partial_compare(&key(a), &key(b)));
+}
+
+pub(crate) fn tim_probed<T, F, P>(list: &mut [T], mut compare: F, probe: &P)
+    where F: FnMut(&T, &T) -> Ordering, P: Probe<T> + ?Sized {
+    let n = list.len();
+    if n < 2 {
+        return;
+    }
+
+    let min_run = min_run(n);
+    let mut state = State { runs: Vec::new(), buf: Vec::new(), min_gallop: MIN_GALLOP, probe };
+    let mut lo = 0;
+    while lo < n {
+        let mut len = count_run(&mut list[lo..], &mut compare, probe);
+        if len < min_run {
+            let end = n.min(lo + min_run);
+            binary_insertion(&mut list[lo..end], len, &mut compare, probe);
+            len = end - lo;
+        }
+        state.runs.push(Run { start: lo, len });
+        state.merge_collapse(list, &mut compare);
+        lo += len;
+    }
+    state.merge_force_collapse(list, &mut compare);
+}
+
+// Minimum run length for a list of n elements, chosen so that n/min_run is a power of two or slightly less.
+fn min_run(mut n: usize) -> usize {
+    let mut r = 0;
+    while n >= MIN_MERGE {
+        r |= n & 1;
+        n >>= 1;
+    }
+    n + r
+}
+
+// Sorts the list with list[..sorted] already in order, inserting each element after the equal ones.
+fn binary_insertion<T, F, P>(list: &mut [T], sorted: usize, compare: &mut F, probe: &P)
+    where F: FnMut(&T, &T) -> Ordering, P: Probe<T> + ?Sized {
+    for i in sorted.max(1)..list.len() {
+        let (head, tail) = list.split_at(i);
+        let pos = head.partition_point(|e| compare(&tail[0], e) != Ordering::Less);
+        list[pos..=i].rotate_right(1);
+        written(&list[pos..=i], probe);
+    }
+}
+
+// Number of leading elements of the list satisfying `before`, the elements satisfying it come first.
+// Exponential search from the front: O(logk) comparisons for a result k.
+fn gallop_front<T, P>(list: &[T], mut before: P) -> usize where P: FnMut(&T) -> bool {
+    let n = list.len();
+    let mut lo = 0;
+    let mut ofs = 0;
+    while ofs < n && before(&list[ofs]) {
+        lo = ofs + 1;
+        ofs = 2*ofs + 1;
+    }
+    let hi = ofs.min(n);
+    lo + list[lo..hi].partition_point(before)
+}
+
+// Same as `gallop_front`, searching from the back: O(logk) comparisons for k elements not satisfying `before`.
+fn gallop_back<T, P>(list: &[T], mut before: P) -> usize where P: FnMut(&T) -> bool {
+    let n = list.len();
+    let mut hi = n;
+    let mut ofs = 0;
+    while ofs < n && !before(&list[n-1-ofs]) {
+        hi = n - 1 - ofs;
+        ofs = 2*ofs + 1;
+    }
+    let lo = n - ofs.min(n);
+    lo + list[lo..hi].partition_point(before)
+}
+
+#[derive(Clone, Copy)]
+struct Run {
+    start: usize,
+    len: usize,
+}
+
+struct State<'a, T, P> where P: ?Sized {
+    // pending runs, consecutive in the list
+    runs: Vec<Run>,
+    buf: Vec<T>,
+    min_gallop: usize,
+    probe: &'a P,
+}
+
+impl<'a, T, P> State<'a, T, P> where P: Probe<T> + ?Sized {
+    // Merges runs until the stack invariants hold: every run is longer than the sum of the next two
+    // and longer than the next one, so run lengths grow at least as fast as Fibonacci numbers.
+    fn merge_collapse<F>(&mut self, list: &mut [T], compare: &mut F) where F: FnMut(&T, &T) -> Ordering {
+        while self.runs.len() > 1 {
+            let runs = &self.runs;
+            let mut n = runs.len() - 2;
+            if (n > 0 && runs[n-1].len <= runs[n].len + runs[n+1].len)
+                || (n > 1 && runs[n-2].len <= runs[n-1].len + runs[n].len) {
+                if runs[n-1].len < runs[n+1].len {
+                    n -= 1;
+                }
+            } else if runs[n].len > runs[n+1].len {
+                break;
+            }
+            self.merge_at(n, list, compare);
+        }
+    }
+
+    fn merge_force_collapse<F>(&mut self, list: &mut [T], compare: &mut F) where F: FnMut(&T, &T) -> Ordering {
+        while self.runs.len() > 1 {
+            let mut n = self.runs.len() - 2;
+            if n > 0 && self.runs[n-1].len < self.runs[n+1].len {
+                n -= 1;
+            }
+            self.merge_at(n, list, compare);
+        }
+    }
+
+    // Merges runs i and i+1 of the stack.
+    fn merge_at<F>(&mut self, i: usize, list: &mut [T], compare: &mut F) where F: FnMut(&T, &T) -> Ordering {
+        let a = self.runs[i];
+        let b = self.runs.remove(i+1);
+        self.runs[i].len += b.len;
+
+        let list = &mut list[a.start..b.start+b.len];
+        let mut mid = a.len;
+
+        // elements of the left run not greater than the first of the right run are in place
+        let skip = {
+            let l: &[T] = list;
+            gallop_front(&l[..mid], |e| compare(e, &l[mid]) != Ordering::Greater)
+        };
+        let list = &mut list[skip..];
+        mid -= skip;
+        if mid == 0 {
+            return;
+        }
+
+        // elements of the right run not less than the last of the left run are in place
+        let keep = {
+            let l: &[T] = list;
+            gallop_back(&l[mid..], |e| compare(e, &l[mid-1]) == Ordering::Less)
+        };
+        let list = &mut list[..mid+keep];
+
+        if mid <= keep {
+            merge_lo(list, mid, scratch(&mut self.buf, mid), &mut self.min_gallop, compare, self.probe);
+        } else {
+            merge_hi(list, mid, scratch(&mut self.buf, keep), &mut self.min_gallop, compare, self.probe);
+        }
+    }
+}
+
+// Merges list[..mid] and list[mid..] moving the shorter left run to the buffer and filling the list from the front.
+// The first element of the right run is less than the first of the left run.
+fn merge_lo<T, F, P>(list: &mut [T], mid: usize, buf: &mut [MaybeUninit<T>], min_gallop: &mut usize, compare: &mut F, probe: &P)
+    where F: FnMut(&T, &T) -> Ordering, P: Probe<T> + ?Sized {
+    let mut hole = MergeHole::new(list, &mut buf[..mid], probe);
+
+    'outer: loop {
+        let mut count_a = 0;
+        let mut count_b = 0;
+
+        // one element at a time until one run wins consistently
+        loop {
+            if hole.left().is_empty() || hole.right().is_empty() {
+                break 'outer;
+            }
+            // ties go to the left run, as in `merge_internal`
+            if compare(&hole.right()[0], &hole.left()[0]) == Ordering::Less {
+                hole.take_right(1);
+                count_b += 1;
+                count_a = 0;
+            } else {
+                hole.take_left(1);
+                count_a += 1;
+                count_b = 0;
+            }
+            if count_a.max(count_b) >= *min_gallop {
+                break;
+            }
+        }
+
+        // galloping: moving whole blocks found by exponential search
+        loop {
+            if hole.left().is_empty() || hole.right().is_empty() {
+                break 'outer;
+            }
+            count_a = gallop_front(hole.left(), |e| compare(e, &hole.right()[0]) != Ordering::Greater);
+            hole.take_left(count_a);
+            if hole.left().is_empty() {
+                break 'outer;
+            }
+
+            count_b = gallop_front(hole.right(), |e| compare(e, &hole.left()[0]) == Ordering::Less);
+            hole.take_right(count_b);
+
+            *min_gallop = min_gallop.saturating_sub(1).max(1);
+            if count_a < MIN_GALLOP && count_b < MIN_GALLOP {
+                break;
+            }
+        }
+        // penalty for leaving galloping mode
+        *min_gallop += 2;
+    }
+}
+
+// Merges list[..mid] and list[mid..] moving the shorter right run to the buffer and filling the list from the back.
+// The last element of the left run is greater than the last of the right run.
+fn merge_hi<T, F, P>(list: &mut [T], mid: usize, buf: &mut [MaybeUninit<T>], min_gallop: &mut usize, compare: &mut F, probe: &P)
+    where F: FnMut(&T, &T) -> Ordering, P: Probe<T> + ?Sized {
+    let len_b = list.len() - mid;
+    let mut hole = MergeHoleHi::new(list, &mut buf[..len_b], probe);
+
+    'outer: loop {
+        let mut count_a = 0;
+        let mut count_b = 0;
+
+        loop {
+            let (left, right) = (hole.left(), hole.right());
+            if left.is_empty() || right.is_empty() {
+                break 'outer;
+            }
+            // filling from the back, ties go to the right run
+            if compare(&right[right.len()-1], &left[left.len()-1]) == Ordering::Less {
+                hole.take_left(1);
+                count_a += 1;
+                count_b = 0;
+            } else {
+                hole.take_right(1);
+                count_b += 1;
+                count_a = 0;
+            }
+            if count_a.max(count_b) >= *min_gallop {
+                break;
+            }
+        }
+
+        loop {
+            let (left, right) = (hole.left(), hole.right());
+            if left.is_empty() || right.is_empty() {
+                break 'outer;
+            }
+            count_a = left.len() - gallop_back(left, |e| compare(e, &right[right.len()-1]) != Ordering::Greater);
+            hole.take_left(count_a);
+            if hole.left().is_empty() {
+                break 'outer;
+            }
+
+            let (left, right) = (hole.left(), hole.right());
+            count_b = right.len() - gallop_back(right, |e| compare(e, &left[left.len()-1]) == Ordering::Less);
+            hole.take_right(count_b);
+
+            *min_gallop = min_gallop.saturating_sub(1).max(1);
+            if count_a < MIN_GALLOP && count_b < MIN_GALLOP {
+                break;
+            }
+        }
+        *min_gallop += 2;
+    }
+    // dropping the hole moves the rest of the right run back
+}
+
+// Right run moved out to the buffer while merging from the back. The gap list[a..a+j] always has room
+// for buf[..j], dropping the hole (also when a comparator panics) moves the remaining elements back.
+struct MergeHoleHi<'a, T, P> where P: Probe<T> + ?Sized {
+    list: &'a mut [T],
+    buf: &'a mut [MaybeUninit<T>],
+    a: usize,
+    j: usize,
+    probe: &'a P,
+}
+
+impl<'a, T, P> MergeHoleHi<'a, T, P> where P: Probe<T> + ?Sized {
+    // Moves the right run list[list.len()-buf.len()..] out to the buffer.
+    fn new(list: &'a mut [T], buf: &'a mut [MaybeUninit<T>], probe: &'a P) -> Self {
+        let (a, j) = (list.len() - buf.len(), buf.len());
+        unsafe {
+            ptr::copy_nonoverlapping(list[a..].as_ptr(), buf.as_mut_ptr() as *mut T, j);
+        }
+        MergeHoleHi { list, buf, a, j, probe }
+    }
+
+    // Elements of the left run not merged yet.
+    fn left(&self) -> &[T] {
+        &self.list[..self.a]
+    }
+
+    // Elements of the right run not merged yet.
+    fn right(&self) -> &[T] {
+        // buf[..j] holds the elements moved out of the list
+        unsafe { &*(&self.buf[..self.j] as *const [MaybeUninit<T>] as *const [T]) }
+    }
+
+    // Moves the last `count` elements of the left run to the back of the gap.
+    fn take_left(&mut self, count: usize) {
+        let (a, k) = (self.a - count, self.a + self.j - count);
+        unsafe {
+            let p = self.list.as_mut_ptr();
+            ptr::copy(p.add(a), p.add(k), count);
+        }
+        self.a -= count;
+        written(&self.list[k..k+count], self.probe);
+    }
+
+    // Moves the last `count` elements of the right run into the gap.
+    fn take_right(&mut self, count: usize) {
+        let (j, k) = (self.j - count, self.a + self.j - count);
+        let src = &self.buf[j..j+count];
+        let dst = &mut self.list[k..k+count];
+        unsafe {
+            ptr::copy_nonoverlapping(src.as_ptr() as *const T, dst.as_mut_ptr(), count);
+        }
+        self.j -= count;
+        written(&self.list[k..k+count], self.probe);
+    }
+}
+
+impl<'a, T, P> Drop for MergeHoleHi<'a, T, P> where P: Probe<T> + ?Sized {
+    fn drop(&mut self) {
+        let (a, j) = (self.a, self.j);
+        unsafe {
+            ptr::copy_nonoverlapping(self.buf.as_ptr() as *const T, self.list[a..a+j].as_mut_ptr(), j);
+        }
+        written(&self.list[a..a+j], self.probe);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check_order<T>(list: &[T]) where T: PartialOrd + std::fmt::Debug {
+        let mut prev: &T = &list[0];
+        for a in list.iter() {
+            assert!(a >= prev, "non-decreasing order violation: {:?}", list);
+            prev = a;
+        }
+    }
+
+    #[test]
+    fn tim_two_elements() {
+        let mut input = vec![2, 1];
+        tim(&mut input);
+        check_order(&input);
+    }
+
+    #[test]
+    fn tim_min_run() {
+        assert_eq!(min_run(63), 63);
+        assert_eq!(min_run(64), 32);
+        assert_eq!(min_run(65), 33);
+        assert_eq!(min_run(1 << 20), 32);
+        for n in 64..5000 {
+            assert!((32..=64).contains(&min_run(n)), "{}", n);
+        }
+    }
+
+    #[test]
+    fn tim_patterns() {
+        let sawtooth: Vec<i32> = (0..3000).map(|i| i % 37).collect();
+        let organ_pipe: Vec<i32> = (0..1500).chain((0..1500).rev()).collect();
+        let pseudo_random: Vec<i32> = (0..3000).map(|i| (i * 7919) % 3001).collect();
+        let descending: Vec<i32> = (0..3000).rev().collect();
+        let blocks: Vec<i32> = (0..3000).map(|i| (i % 500) * 6 + i / 500).collect();
+        for input in [sawtooth, organ_pipe, pseudo_random, descending, blocks].iter_mut() {
+            let mut expected = input.clone();
+            expected.sort();
+            tim(input);
+            assert_eq!(input, &expected);
+        }
+    }
+
+    #[test]
+    fn tim_stable() {
+        let mut input: Vec<(i32, usize)> = (0..5000).map(|i| (((i * 7919) % 5003 % 13) as i32, i)).collect();
+        let mut expected = input.clone();
+        expected.sort_by_key(|e| e.0);
+        tim_by_key(&mut input, |e| e.0);
+        assert_eq!(input, expected);
+    }
+
+    #[test]
+    fn tim_by_desc() {
+        let mut input: Vec<i32> = (0..200).collect();
+        tim_by(&mut input, |a, b| b.cmp(a));
+        assert_eq!(input, (0..200).rev().collect::<Vec<i32>>());
+    }
+
+    #[test]
+    fn tim_linear_on_presorted() {
+        let n = 10000;
+        let inputs: Vec<Vec<i32>> = vec![(0..n).collect(), (0..n).rev().collect(), vec![1; n as usize]];
+        for mut input in inputs {
+            let mut comparisons = 0;
+            tim_by(&mut input, |a, b| {
+                comparisons += 1;
+                a.cmp(b)
+            });
+            check_order(&input);
+            assert!(comparisons <= n, "{} comparisons", comparisons);
+        }
+    }
+
+    #[test]
+    fn tim_gallops_on_interleaved_blocks() {
+        // two sorted halves made of long blocks of each other: detecting the runs takes n comparisons,
+        // merging them only a few per block
+        let n = 20000;
+        let mut input: Vec<i32> = (0..n).filter(|i| (i / 1000) % 2 == 0)
+            .chain((0..n).filter(|i| (i / 1000) % 2 == 1))
+            .collect();
+        let mut comparisons = 0;
+        tim_by(&mut input, |a, b| {
+            comparisons += 1;
+            a.cmp(b)
+        });
+        assert_eq!(input, (0..n).collect::<Vec<i32>>());
+        assert!(comparisons < n + n / 20, "{} comparisons", comparisons);
+    }
+}
@@ -0,0 +1,352 @@
+//! Step-by-step traces of the sorts for visualization: every comparison, swap and write is recorded
+//! with the indices involved, and the trace can be exported as JSON lines or as SVG bar chart frames.
+use std::cell::RefCell;
+use std::fmt::{self, Write as _};
+use std::io::{self, Write};
+use std::mem;
+use crate::sort::probe::Probe;
+use crate::sort::sorter::Sorter;
+
+// Bar colors of the SVG frames.
+const BAR: &str = "#9e9e9e";
+const COMPARED: &str = "#fbc02d";
+const SWAPPED: &str = "#e53935";
+const WRITTEN: &str = "#1e88e5";
+
+/// Operation made by a sort. Indices are positions in the slice, `None` stands for an element
+/// outside of it, like a buffer slot or a saved pivot.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event<T> {
+    Compare(Option<usize>, Option<usize>),
+    Swap(usize, usize),
+    /// The element moved into the position.
+    Write(usize, T),
+}
+
+/// Recorded run of a sort: the input and the operations made on it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trace<T> {
+    pub initial: Vec<T>,
+    pub events: Vec<Event<T>>,
+}
+
+/// Sorts the list with the algorithm recording every operation. Elements of a zero-sized type
+/// can not be told apart, so only their comparisons are recorded, without positions.
+pub fn record<T>(sorter: &dyn Sorter<T>, list: &mut [T]) -> Trace<T> where T: Clone {
+    let recorder = Recorder {
+        base: list.as_ptr() as usize,
+        len: list.len(),
+        events: RefCell::new(Vec::new()),
+    };
+    let initial = list.to_vec();
+    sorter.sort_probed(list, &recorder);
+    Trace { initial, events: recorder.events.into_inner() }
+}
+
+impl<T> Trace<T> where T: Clone {
+    /// State of the slice after the first `steps` events.
+    pub fn replay(&self, steps: usize) -> Vec<T> {
+        let mut state = self.initial.clone();
+        for event in self.events[..steps].iter() {
+            apply(&mut state, event);
+        }
+        state
+    }
+}
+
+impl<T> Trace<T> where T: fmt::Display {
+    /// Writes the trace as JSON lines: an `init` line with the input followed by one line per event.
+    /// Values are written as numbers when they print as a valid JSON number and as strings otherwise.
+    pub fn write_json_lines<W>(&self, mut out: W) -> io::Result<()> where W: Write {
+        let values: Vec<String> = self.initial.iter().map(json_value).collect();
+        writeln!(out, "{{\"op\":\"init\",\"values\":[{}]}}", values.join(","))?;
+        for (step, event) in self.events.iter().enumerate() {
+            match event {
+                Event::Compare(i, j) => writeln!(out, "{{\"step\":{},\"op\":\"compare\",\"i\":{},\"j\":{}}}",
+                    step, json_index(*i), json_index(*j))?,
+                Event::Swap(i, j) => writeln!(out, "{{\"step\":{},\"op\":\"swap\",\"i\":{},\"j\":{}}}", step, i, j)?,
+                Event::Write(i, v) => writeln!(out, "{{\"step\":{},\"op\":\"write\",\"i\":{},\"value\":{}}}",
+                    step, i, json_value(v))?,
+            }
+        }
+        out.flush()
+    }
+}
+
+impl<T> Trace<T> {
+    /// Renders the input and the state after every event as SVG bar charts of the given size
+    /// in pixels, `bar` gives the height of the bar of a value on any scale. Bars touched by
+    /// the event are highlighted.
+    pub fn svg_frames<F>(&self, width: u32, height: u32, mut bar: F) -> Vec<String> where F: FnMut(&T) -> f64 {
+        let values: Vec<f64> = self.initial.iter().map(&mut bar).collect();
+        let lo = values.iter().cloned().fold(0.0, f64::min);
+        let mut hi = values.iter().cloned().fold(lo, f64::max);
+        if hi <= lo {
+            hi = lo + 1.0;
+        }
+        let chart = Chart { width: width as f64, height: height as f64, lo, hi };
+
+        let mut state = values;
+        let mut frames = Vec::with_capacity(self.events.len() + 1);
+        frames.push(chart.render(&state, &[]));
+        for event in self.events.iter() {
+            let marked = match event {
+                Event::Compare(i, j) => [*i, *j].iter().flatten().map(|&k| (k, COMPARED)).collect(),
+                Event::Swap(i, j) => {
+                    state.swap(*i, *j);
+                    vec![(*i, SWAPPED), (*j, SWAPPED)]
+                }
+                Event::Write(i, v) => {
+                    state[*i] = bar(v);
+                    vec![(*i, WRITTEN)]
+                }
+            };
+            frames.push(chart.render(&state, &marked));
+        }
+        frames
+    }
+}
+
+fn apply<T>(state: &mut [T], event: &Event<T>) where T: Clone {
+    match event {
+        Event::Compare(_, _) => {}
+        Event::Swap(i, j) => state.swap(*i, *j),
+        Event::Write(i, v) => state[*i] = v.clone(),
+    }
+}
+
+fn json_index(i: Option<usize>) -> String {
+    i.map_or_else(|| String::from("null"), |i| i.to_string())
+}
+
+fn json_value<T>(v: &T) -> String where T: fmt::Display {
+    let s = v.to_string();
+    if is_json_number(&s) {
+        return s;
+    }
+    let mut quoted = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            c if (c as u32) < 0x20 => write!(quoted, "\\u{:04x}", c as u32).unwrap(),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+// Checks the JSON number grammar: -?(0|[1-9][0-9]*)(.[0-9]+)?([eE][+-]?[0-9]+)?
+fn is_json_number(s: &str) -> bool {
+    fn digits(b: &[u8]) -> usize {
+        b.iter().take_while(|c| c.is_ascii_digit()).count()
+    }
+
+    let mut b = s.as_bytes();
+    if b.first() == Some(&b'-') {
+        b = &b[1..];
+    }
+    let n = digits(b);
+    if n == 0 || (n > 1 && b[0] == b'0') {
+        return false;
+    }
+    b = &b[n..];
+    if b.first() == Some(&b'.') {
+        let n = digits(&b[1..]);
+        if n == 0 {
+            return false;
+        }
+        b = &b[1+n..];
+    }
+    if let Some(b'e') | Some(b'E') = b.first() {
+        b = &b[1..];
+        if let Some(b'+') | Some(b'-') = b.first() {
+            b = &b[1..];
+        }
+        let n = digits(b);
+        if n == 0 {
+            return false;
+        }
+        b = &b[n..];
+    }
+    b.is_empty()
+}
+
+struct Chart {
+    width: f64,
+    height: f64,
+    lo: f64,
+    hi: f64,
+}
+
+impl Chart {
+    fn render(&self, values: &[f64], marked: &[(usize, &str)]) -> String {
+        let mut svg = format!("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n",
+            w = self.width, h = self.height);
+        let bar = self.width / values.len().max(1) as f64;
+        for (k, v) in values.iter().enumerate() {
+            let fill = marked.iter().rev().find(|m| m.0 == k).map_or(BAR, |m| m.1);
+            let h = (v - self.lo) / (self.hi - self.lo) * self.height;
+            writeln!(svg, "<rect x=\"{:.2}\" y=\"{:.2}\" width=\"{:.2}\" height=\"{:.2}\" fill=\"{}\"/>",
+                k as f64 * bar, self.height - h, bar, h, fill).unwrap();
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+// Probe turning the element addresses into positions of the traced slice.
+struct Recorder<T> {
+    base: usize,
+    len: usize,
+    events: RefCell<Vec<Event<T>>>,
+}
+
+impl<T> Recorder<T> {
+    fn index(&self, e: &T) -> Option<usize> {
+        let size = mem::size_of::<T>();
+        let addr = e as *const T as usize;
+        if size == 0 || addr < self.base || addr >= self.base + self.len * size {
+            return None;
+        }
+        Some((addr - self.base) / size)
+    }
+
+    fn position(&self, e: &T) -> usize {
+        self.index(e).expect("element outside of the traced slice")
+    }
+}
+
+impl<T> Probe<T> for Recorder<T> where T: Clone {
+    fn compare(&self, a: &T, b: &T) {
+        self.events.borrow_mut().push(Event::Compare(self.index(a), self.index(b)));
+    }
+
+    fn swap(&self, a: &T, b: &T) {
+        if mem::size_of::<T>() == 0 {
+            return;
+        }
+        self.events.borrow_mut().push(Event::Swap(self.position(a), self.position(b)));
+    }
+
+    fn write(&self, dst: &T) {
+        if mem::size_of::<T>() == 0 {
+            return;
+        }
+        self.events.borrow_mut().push(Event::Write(self.position(dst), dst.clone()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sort::sorter::*;
+
+    #[test]
+    fn trace_bubble_events() {
+        let mut list = vec![3, 1, 2];
+        let trace = record(&BubbleSort, &mut list);
+        assert_eq!(trace.initial, vec![3, 1, 2]);
+        assert_eq!(trace.events, vec![
+            Event::Compare(Some(1), Some(0)),
+            Event::Swap(0, 1),
+            Event::Compare(Some(2), Some(1)),
+            Event::Swap(1, 2),
+            Event::Compare(Some(1), Some(0)),
+        ]);
+        assert_eq!(trace.replay(2), vec![1, 3, 2]);
+    }
+
+    #[test]
+    fn trace_replays_to_sorted() {
+        let input: Vec<i32> = (0..200).map(|i| (i * 7919) % 211).collect();
+        let mut sorted = input.clone();
+        sorted.sort();
+
+        let r: Registry<i32> = Registry::standard();
+        for s in r.iter() {
+            let mut list = input.clone();
+            let trace = record(s, &mut list);
+            assert_eq!(trace.replay(trace.events.len()), sorted, "{}", s.name());
+        }
+
+        let mut list: Vec<i32> = (0..30).map(|i| (i * 7) % 3).collect();
+        let trace = record(&FlagSort, &mut list);
+        assert_eq!(trace.replay(trace.events.len()), list);
+        // the pivot of the flag sort is not a slice element
+        assert!(trace.events.iter().all(|e| !matches!(e, Event::Compare(_, Some(_)))));
+    }
+
+    #[test]
+    fn trace_zero_sized() {
+        let r: Registry<()> = Registry::standard();
+        for s in r.iter() {
+            let mut list = vec![(); 5];
+            let trace = record(s, &mut list);
+            assert!(trace.events.iter().all(|e| *e == Event::Compare(None, None)), "{}", s.name());
+            assert_eq!(trace.replay(trace.events.len()), vec![(); 5]);
+        }
+        assert!(!record(&QuickSort, &mut [(); 5]).events.is_empty());
+    }
+
+    #[test]
+    fn trace_merge_writes_from_buffer() {
+        let mut list = vec![4, 3, 2, 1];
+        let trace = record(&MergeSort, &mut list);
+        assert!(trace.events.contains(&Event::Compare(Some(1), None)));
+        assert!(trace.events.contains(&Event::Write(2, 3)));
+    }
+
+    #[test]
+    fn trace_json_lines() {
+        let mut list = vec![2, 1];
+        let trace = record(&InsertionSort, &mut list);
+        let mut out = Vec::new();
+        trace.write_json_lines(&mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "{\"op\":\"init\",\"values\":[2,1]}\n\
+            {\"step\":0,\"op\":\"compare\",\"i\":1,\"j\":0}\n\
+            {\"step\":1,\"op\":\"swap\",\"i\":1,\"j\":0}\n");
+
+        let trace = Trace { initial: vec!["a\"b"], events: vec![Event::Compare(None, Some(0))] };
+        let mut out = Vec::new();
+        trace.write_json_lines(&mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "{\"op\":\"init\",\"values\":[\"a\\\"b\"]}\n\
+            {\"step\":0,\"op\":\"compare\",\"i\":null,\"j\":0}\n");
+    }
+
+    #[test]
+    fn trace_svg_frames() {
+        let mut list = vec![3, 1, 2];
+        let trace = record(&SelectionSort, &mut list);
+        let frames = trace.svg_frames(300, 100, |&v| v as f64);
+        assert_eq!(frames.len(), trace.events.len() + 1);
+        for frame in frames.iter() {
+            assert!(frame.starts_with("<svg"));
+            assert_eq!(frame.matches("<rect").count(), 3);
+        }
+        // the first bar is the tallest one at the start
+        assert!(frames[0].contains("<rect x=\"0.00\" y=\"0.00\" width=\"100.00\" height=\"100.00\" fill=\"#9e9e9e\"/>"));
+        assert!(frames.iter().any(|f| f.contains(SWAPPED)));
+
+        let mut list: Vec<u64> = vec![u64::MAX, 0];
+        let trace = record(&InsertionSort, &mut list);
+        let frames = trace.svg_frames(200, 100, |&v| v as f64);
+        assert!(frames[2].contains("<rect x=\"100.00\" y=\"0.00\" width=\"100.00\" height=\"100.00\""));
+    }
+
+    #[test]
+    fn trace_json_number_grammar() {
+        for s in ["0", "-0", "7", "-12", "3.25", "1e5", "2.5E-3", "-0.0e+10"].iter() {
+            assert!(is_json_number(s), "{}", s);
+        }
+        for s in ["", "-", "007", ".5", "5.", "+1", "1e", "1e+", "NaN", "inf", "0x10", "1_000", " 1"].iter() {
+            assert!(!is_json_number(s), "{}", s);
+        }
+
+        let trace: Trace<String> = Trace { initial: vec!["007".into(), ".5".into(), "5.".into(), "-1.5".into()], events: vec![] };
+        let mut out = Vec::new();
+        trace.write_json_lines(&mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "{\"op\":\"init\",\"values\":[\"007\",\".5\",\"5.\",-1.5]}\n");
+    }
+}
@@ -13,6 +13,7 @@ const MAX_FILL_PERCENT: f32 = 1.0/4.0;
 const MIN_FILL_PERCENT: f32 = 1.0/8.0;
 
 impl<T> HashTable<T> {
+    #[allow(clippy::new_without_default)]
     pub fn new() -> Self {
         HashTable::new_size(INIT_SIZE)
     }
@@ -49,10 +50,10 @@ impl<T> HashTable<T> {
 
     fn rehash(&mut self, newsize: usize) {
         let mut h = HashTable::new_size(newsize);
-        let keys = mem::replace(&mut self.keys, Vec::new());
-        let data = mem::replace(&mut self.data, Vec::new());
+        let keys = mem::take(&mut self.keys);
+        let data = mem::take(&mut self.data);
 
-        for (k, v) in keys.into_iter().zip(data.into_iter()) {
+        for (k, v) in keys.into_iter().zip(data) {
             match k {
                 None => continue,
                 Some(key) => {
@@ -87,7 +88,7 @@ impl<T> HashTable<T> {
 
     pub fn delete(&mut self, key: usize) {
         let mut index = self.get_index(key);
-        if !self.keys[index].is_some() {
+        if self.keys[index].is_none() {
             return;
         }
 
@@ -110,6 +111,7 @@ impl<T> HashTable<T> {
         }
     }
 
+    #[allow(clippy::len_without_is_empty)]
     pub fn len(&self) -> usize {
         self.count
     }
@@ -148,9 +150,9 @@ mod tests {
         h.set(1, 5);
         assert_eq!(h.get_mut(1), Some(&mut 5));
 
-        h.get_mut(1).map(|val| {
+        if let Some(val) = h.get_mut(1) {
             *val = 6;
-        });
+        }
         assert_eq!(h.get(1), Some(&6));
     }
 
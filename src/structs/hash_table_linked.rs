@@ -15,6 +15,7 @@ type Link<T> = Option<Box<Node<T>>>;
 const INIT_SIZE: usize = 997;
 
 impl<T> HashTableLinked<T> {
+    #[allow(clippy::new_without_default)]
     pub fn new() -> Self {
         HashTableLinked::new_size(INIT_SIZE)
     }
@@ -87,7 +88,7 @@ impl<T> HashTableLinked<T> {
                 if x.key == key {
                     return Some(&x.value);
                 }
-                return None;
+                None
             }
         }
     }
@@ -110,7 +111,7 @@ impl<T> HashTableLinked<T> {
                 if x.key == key {
                     return Some(&mut x.value);
                 }
-                return None;
+                None
             }
         }
     }
@@ -179,9 +180,9 @@ mod test {
         h.set(0, 0);
         assert_eq!(h.get_mut(0), Some(&mut 0));
 
-        h.get_mut(0).map(|val| {
+        if let Some(val) = h.get_mut(0) {
             *val = 1;
-        });
+        }
         assert_eq!(h.get(0), Some(&1));
     }
 
@@ -123,7 +123,7 @@ mod test {
 
     #[test]
     fn from_list() {
-        let h = Heap::from(&vec![2, 3, 1]);
+        let h = Heap::from(&[2, 3, 1]);
 
         assert_eq!(h.top(), 1);
         assert_eq!(h.size(), 3);
@@ -11,6 +11,7 @@ struct Node<T> {
 }
 
 impl<T> StackLinked<T> {
+    #[allow(clippy::new_without_default)]
     pub fn new() -> Self {
         StackLinked {
             head: Link::None,
@@ -58,6 +59,7 @@ impl<T> Drop for StackLinked<T> {
 pub struct IntoIter<T>(StackLinked<T>);
 
 impl<T> StackLinked<T> {
+    #[allow(clippy::should_implement_trait)]
     pub fn into_iter(self) -> IntoIter<T> {
         IntoIter(self)
     }
@@ -73,12 +75,12 @@ impl<T> Iterator for IntoIter<T> {
 
 
 pub struct Iter<'a, T> {
-    next: Option<&'a Box<Node<T>>>,
+    next: Option<&'a Node<T>>,
 }
 
 impl<T> StackLinked<T> {
-    pub fn iter(&self) -> Iter<T> {
-        Iter { next: self.head.as_ref() }
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter { next: self.head.as_deref() }
     }
 }
 
@@ -87,7 +89,7 @@ impl<'a, T> Iterator for Iter<'a, T> {
 
     fn next(&mut self) -> Option<Self::Item> {
         self.next.take().map(|node| {
-            self.next = node.next.as_ref();
+            self.next = node.next.as_deref();
             &node.val
         })
     }
@@ -99,7 +101,7 @@ pub struct IterMut<'a, T> {
 }
 
 impl<T> StackLinked<T> {
-    pub fn iter_mut(&mut self) -> IterMut<T> {
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
         IterMut{ next: self.head.as_mut() }
     }
 }
@@ -141,9 +143,9 @@ mod tests {
         let mut s = StackLinked::new();
         s.push(1);
         assert_eq!(s.peek(), Some(&1));
-        s.peek_mut().map(|val| {
+        if let Some(val) = s.peek_mut() {
             *val = 2;
-        });
+        }
         assert_eq!(s.peek(), Some(&2));
     }
 
@@ -15,7 +15,7 @@ impl UnionFind {
         }
 
         UnionFind {
-            data: data,
+            data,
             size: vec![1; size],
             count: size,
         }
@@ -81,11 +81,11 @@ mod tests {
         assert_eq!(uf.count(), 2);
         assert_eq!(uf.find(0), 0);
         assert_eq!(uf.find(1), 1);
-        assert_eq!(uf.connected(0, 1), false);
+        assert!(!uf.connected(0, 1));
 
         uf.union(0, 1);
         assert_eq!(uf.count(), 1);
-        assert_eq!(uf.connected(0, 1), true);
+        assert!(uf.connected(0, 1));
     }
 
     #[test]
@@ -94,13 +94,13 @@ mod tests {
         uf.union(0, 1);
         uf.union(2, 3);
 
-        assert_eq!(uf.connected(0, 1), true);
-        assert_eq!(uf.connected(2, 3), true);
-        assert_eq!(uf.connected(0, 3), false);
+        assert!(uf.connected(0, 1));
+        assert!(uf.connected(2, 3));
+        assert!(!uf.connected(0, 3));
 
         uf.union(1, 2);
         assert_eq!(uf.count(), 1);
-        assert_eq!(uf.connected(0, 3), true);
+        assert!(uf.connected(0, 3));
     }
 
     #[test]
@@ -114,6 +114,6 @@ mod tests {
 
         uf.union(5, 0);
         assert_eq!(uf.count(), 2);
-        assert_eq!(uf.connected(1, 5), true);
+        assert!(uf.connected(1, 5));
     }
 }
\ No newline at end of file